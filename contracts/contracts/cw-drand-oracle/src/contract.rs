@@ -1,25 +1,42 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult,
 };
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::BEACONS;
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use crate::state::{Config, BEACONS, CONFIG};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:drand-oracle";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const DELIVERY_REPLY_ID: u64 = 1;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
     _info: MessageInfo,
-    _msg: InstantiateMsg,
+    msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let network = msg.network.unwrap_or_default();
+    if network.period == 0 {
+        return Err(ContractError::InvalidNetwork);
+    }
+
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            fee_denom: msg.fee_denom,
+            base_fee: msg.base_fee,
+            gas_price: msg.gas_price,
+            network,
+        },
+    )?;
     Ok(Response::default())
 }
 
@@ -35,8 +52,56 @@ pub fn execute(
             round,
             signature,
             randomness,
-        } => execute::add_beacon(deps, round, signature, randomness),
+        } => execute::add_beacon(deps, info, round, signature, randomness),
         ExecuteMsg::NextBeacon => execute::next_beacon(deps, env, info),
+        ExecuteMsg::Deposit { consumer } => execute::deposit(deps, info, consumer),
+        ExecuteMsg::Withdraw { amount } => execute::withdraw(deps, info, amount),
+        ExecuteMsg::Subscribe {
+            schedule,
+            gas_limit,
+        } => execute::subscribe(deps, env, info, schedule, gas_limit),
+        ExecuteMsg::Unsubscribe {} => execute::unsubscribe(deps, info),
+        ExecuteMsg::Deliver { round, limit } => execute::deliver(deps, info, round, limit),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    if !CONFIG.exists(deps.storage) {
+        let fee_denom = msg
+            .fee_denom
+            .ok_or_else(|| StdError::generic_err("fee_denom is required to migrate this oracle"))?;
+        let network = msg.network.unwrap_or_default();
+        if network.period == 0 {
+            return Err(ContractError::InvalidNetwork);
+        }
+
+        CONFIG.save(
+            deps.storage,
+            &Config {
+                fee_denom,
+                base_fee: msg.base_fee.unwrap_or_default(),
+                gas_price: msg.gas_price.unwrap_or_default(),
+                network,
+            },
+        )?;
+    }
+
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("version", CONTRACT_VERSION))
+}
+
+/// Paid deliveries are dispatched with `reply_on_error` so a failing consumer
+/// cannot block a beacon from being stored. The delivery fee is not refunded.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        DELIVERY_REPLY_ID => Ok(Response::new()
+            .add_attribute("action", "delivery_failed")
+            .add_attribute("consumer", String::from_utf8_lossy(msg.payload.as_slice()))),
+        id => Err(StdError::generic_err(format!("unknown reply id: {}", id)).into()),
     }
 }
 
@@ -77,32 +142,56 @@ pub mod verify {
     }
 }
 
+pub mod derive {
+    use sha2::{Digest, Sha256};
+
+    /// Upper bound on the number of values a single derivation may produce
+    pub const MAX_DERIVED_VALUES: u32 = 256;
+
+    /// Derive `count` domain-separated 32-byte values from a beacon's uniform seed.
+    ///
+    /// Value `i` (0-based) is:
+    ///
+    /// ```text
+    /// sha256(uniform_seed || u64_be(len(domain)) || domain || u32_be(i))
+    /// ```
+    ///
+    /// The length prefix keeps distinct `(domain, i)` pairs from colliding, so two
+    /// consumers using different domains never share randomness for the same round.
+    pub fn derive_randomness(uniform_seed: &[u8; 32], domain: &str, count: u32) -> Vec<[u8; 32]> {
+        (0..count)
+            .map(|index| {
+                let mut hasher = Sha256::new();
+                hasher.update(uniform_seed);
+                hasher.update((domain.len() as u64).to_be_bytes());
+                hasher.update(domain.as_bytes());
+                hasher.update(index.to_be_bytes());
+                hasher.finalize().into()
+            })
+            .collect()
+    }
+}
+
 pub mod execute {
     use crate::{
-        msg::ConcreteBeacon,
-        state::{Randomness, DELIVERY_QUEUES},
+        msg::{ConcreteBeacon, Schedule},
+        state::{
+            Randomness, Subscription, BALANCES, DELIVERY_QUEUES, SCHEDULED_DELIVERIES,
+            SUBSCRIPTIONS,
+        },
     };
-    use cosmwasm_std::{HexBinary, SubMsg, Timestamp, Uint64, WasmMsg};
+    use cosmwasm_std::{Addr, BankMsg, Coin, HexBinary, Order, SubMsg, Uint128, Uint64, WasmMsg};
 
     use super::*;
 
-    const GENESIS: Timestamp = Timestamp::from_seconds(1692803367);
-    const PERIOD_IN_NS: u64 = 3_000_000_000;
-
     const GAS_LIMIT: u64 = 10_000_000;
-
-    fn next_round(now: Timestamp) -> u64 {
-        if now < GENESIS {
-            1
-        } else {
-            let from_genesis = now.nanos() - GENESIS.nanos();
-            let periods_since_genesis = from_genesis / PERIOD_IN_NS;
-            periods_since_genesis + 1 + 1 // Second addition to convert to 1-based counting
-        }
-    }
+    const MAX_DELIVERY_GAS_LIMIT: u64 = 50_000_000;
+    /// Upper bound on paid deliveries dispatched by a single `AddBeacon` or `Deliver`
+    pub const MAX_DELIVERIES_PER_CALL: u32 = 30;
 
     pub fn add_beacon(
         deps: DepsMut,
+        info: MessageInfo,
         round: Uint64,
         signature: HexBinary,
         randomness: HexBinary,
@@ -132,7 +221,14 @@ pub mod execute {
             },
         )?;
 
-        let mut response: Response = Response::new();
+        let mut response: Response = Response::new()
+            .add_attribute("action", "add_beacon")
+            .add_attribute("round", round);
+
+        let beacon_msg = cosmwasm_std::to_json_binary(&ConcreteBeacon {
+            round,
+            uniform_seed: verified_randomness,
+        })?;
 
         // Load from the job queue and send the beacon to all receivers
         if let Some(queue) = DELIVERY_QUEUES.may_load(deps.storage, round.u64())? {
@@ -140,10 +236,7 @@ pub mod execute {
                 response = response.add_submessage(
                     SubMsg::new(WasmMsg::Execute {
                         contract_addr: receiver.into(),
-                        msg: cosmwasm_std::to_json_binary(&ConcreteBeacon {
-                            round,
-                            uniform_seed: verified_randomness,
-                        })?,
+                        msg: beacon_msg.clone(),
                         funds: vec![],
                     })
                     .with_gas_limit(GAS_LIMIT),
@@ -154,7 +247,142 @@ pub mod execute {
         // Delete the job queue
         DELIVERY_QUEUES.remove(deps.storage, round.u64());
 
-        Ok(Response::default())
+        deliver_scheduled(
+            deps,
+            &info.sender,
+            round,
+            &beacon_msg,
+            MAX_DELIVERIES_PER_CALL,
+            response,
+        )
+    }
+
+    /// Delivers paid subscriptions for a round whose beacon is already stored.
+    /// Anyone may call this to work through deliveries `AddBeacon` left behind.
+    pub fn deliver(
+        deps: DepsMut,
+        info: MessageInfo,
+        round: Uint64,
+        limit: Option<u32>,
+    ) -> Result<Response, ContractError> {
+        let beacon = BEACONS
+            .may_load(deps.storage, round.u64())?
+            .ok_or(ContractError::NoBeacon { round: round.u64() })?;
+
+        let beacon_msg = cosmwasm_std::to_json_binary(&ConcreteBeacon {
+            round,
+            uniform_seed: beacon.uniform_seed,
+        })?;
+        let limit = limit
+            .unwrap_or(MAX_DELIVERIES_PER_CALL)
+            .min(MAX_DELIVERIES_PER_CALL);
+
+        let response = Response::new()
+            .add_attribute("action", "deliver")
+            .add_attribute("round", round);
+        deliver_scheduled(deps, &info.sender, round, &beacon_msg, limit, response)
+    }
+
+    /// Paid subscriptions: debit up to `limit` consumers scheduled for `round` and
+    /// pay the collected fees to `relayer`
+    fn deliver_scheduled(
+        deps: DepsMut,
+        relayer: &Addr,
+        round: Uint64,
+        beacon_msg: &Binary,
+        limit: u32,
+        mut response: Response,
+    ) -> Result<Response, ContractError> {
+        let mut consumers: Vec<Addr> = SCHEDULED_DELIVERIES
+            .prefix(round.u64())
+            .keys(deps.storage, None, None, Order::Ascending)
+            .take(limit as usize + 1)
+            .collect::<StdResult<_>>()?;
+
+        let more_deliveries = consumers.len() > limit as usize;
+        consumers.truncate(limit as usize);
+
+        if consumers.is_empty() {
+            return Ok(response);
+        }
+
+        let config = CONFIG.load(deps.storage)?;
+        let mut collected = Uint128::zero();
+        let mut delivered = 0u32;
+        let mut lapsed = 0u32;
+
+        for consumer in consumers {
+            SCHEDULED_DELIVERIES.remove(deps.storage, (round.u64(), &consumer));
+
+            let mut subscription = match SUBSCRIPTIONS.may_load(deps.storage, &consumer)? {
+                Some(sub) if sub.next_round == round.u64() => sub,
+                _ => continue,
+            };
+
+            let cost = config.delivery_cost(subscription.gas_limit);
+            let balance = BALANCES
+                .may_load(deps.storage, &consumer)?
+                .unwrap_or_default();
+
+            // Out of funds: the subscription lapses instead of being delivered
+            if balance < cost {
+                SUBSCRIPTIONS.remove(deps.storage, &consumer);
+                lapsed += 1;
+                continue;
+            }
+
+            BALANCES.save(deps.storage, &consumer, &(balance - cost))?;
+            collected += cost;
+            delivered += 1;
+
+            subscription.deliveries += 1;
+            match subscription.schedule {
+                Schedule::Once { .. } => SUBSCRIPTIONS.remove(deps.storage, &consumer),
+                Schedule::Every { interval } => {
+                    // Beacons can be relayed out of order; skip rounds already stored
+                    let mut next_round = round.u64() + interval.u64();
+                    while BEACONS.has(deps.storage, next_round) {
+                        next_round += interval.u64();
+                    }
+                    subscription.next_round = next_round;
+                    SCHEDULED_DELIVERIES.save(
+                        deps.storage,
+                        (subscription.next_round, &consumer),
+                        &(),
+                    )?;
+                    SUBSCRIPTIONS.save(deps.storage, &consumer, &subscription)?;
+                }
+            }
+
+            response = response.add_submessage(
+                SubMsg::reply_on_error(
+                    WasmMsg::Execute {
+                        contract_addr: consumer.to_string(),
+                        msg: beacon_msg.clone(),
+                        funds: vec![],
+                    },
+                    DELIVERY_REPLY_ID,
+                )
+                .with_gas_limit(subscription.gas_limit)
+                .with_payload(consumer.as_bytes().to_vec()),
+            );
+        }
+
+        if !collected.is_zero() {
+            response = response.add_message(BankMsg::Send {
+                to_address: relayer.to_string(),
+                amount: vec![Coin {
+                    denom: config.fee_denom,
+                    amount: collected,
+                }],
+            });
+        }
+
+        Ok(response
+            .add_attribute("paid_deliveries", delivered.to_string())
+            .add_attribute("lapsed_subscriptions", lapsed.to_string())
+            .add_attribute("fees_collected", collected)
+            .add_attribute("more_deliveries", more_deliveries.to_string()))
     }
 
     pub fn next_beacon(
@@ -162,7 +390,10 @@ pub mod execute {
         env: Env,
         info: MessageInfo,
     ) -> Result<Response, ContractError> {
-        let next_round = next_round(env.block.time);
+        let next_round = CONFIG
+            .load(deps.storage)?
+            .network
+            .next_round(env.block.time);
         let mut queue = DELIVERY_QUEUES
             .may_load(deps.storage, next_round)?
             .unwrap_or_default();
@@ -172,21 +403,191 @@ pub mod execute {
 
         Ok(Response::default())
     }
+
+    pub fn deposit(
+        deps: DepsMut,
+        info: MessageInfo,
+        consumer: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        let amount = match info.funds.as_slice() {
+            [coin] if coin.denom == config.fee_denom && !coin.amount.is_zero() => coin.amount,
+            _ => return Err(ContractError::InvalidPayment),
+        };
+
+        let consumer = match consumer {
+            Some(addr) => deps.api.addr_validate(&addr)?,
+            None => info.sender,
+        };
+
+        let balance = BALANCES
+            .may_load(deps.storage, &consumer)?
+            .unwrap_or_default()
+            .checked_add(amount)?;
+        BALANCES.save(deps.storage, &consumer, &balance)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "deposit")
+            .add_attribute("consumer", consumer)
+            .add_attribute("amount", amount)
+            .add_attribute("balance", balance))
+    }
+
+    pub fn withdraw(
+        deps: DepsMut,
+        info: MessageInfo,
+        amount: Option<Uint128>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let balance = BALANCES
+            .may_load(deps.storage, &info.sender)?
+            .unwrap_or_default();
+
+        let amount = amount.unwrap_or(balance);
+        if amount.is_zero() || amount > balance {
+            return Err(ContractError::InsufficientBalance {
+                balance,
+                required: amount,
+            });
+        }
+
+        BALANCES.save(deps.storage, &info.sender, &(balance - amount))?;
+
+        Ok(Response::new()
+            .add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![Coin {
+                    denom: config.fee_denom,
+                    amount,
+                }],
+            })
+            .add_attribute("action", "withdraw")
+            .add_attribute("consumer", info.sender)
+            .add_attribute("amount", amount))
+    }
+
+    pub fn subscribe(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        schedule: Schedule,
+        gas_limit: Uint64,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        let gas_limit = gas_limit.u64();
+        if gas_limit == 0 || gas_limit > MAX_DELIVERY_GAS_LIMIT {
+            return Err(ContractError::InvalidGasLimit {
+                max: MAX_DELIVERY_GAS_LIMIT,
+            });
+        }
+
+        // The first delivery must target a round whose beacon is not yet public
+        let earliest = config.network.next_round(env.block.time);
+        let first_round = match &schedule {
+            Schedule::Once { round } => {
+                let round = round.u64();
+                if round < earliest || BEACONS.has(deps.storage, round) {
+                    return Err(ContractError::InvalidSchedule {
+                        msg: format!("round {} is not in the future", round),
+                    });
+                }
+                round
+            }
+            Schedule::Every { interval } => {
+                let interval = interval.u64();
+                if interval == 0 {
+                    return Err(ContractError::InvalidSchedule {
+                        msg: "interval must be at least 1".to_string(),
+                    });
+                }
+                let mut round = earliest.div_ceil(interval) * interval;
+                while BEACONS.has(deps.storage, round) {
+                    round += interval;
+                }
+                round
+            }
+        };
+
+        // Require enough balance for at least the first delivery
+        let cost = config.delivery_cost(gas_limit);
+        let balance = BALANCES
+            .may_load(deps.storage, &info.sender)?
+            .unwrap_or_default();
+        if balance < cost {
+            return Err(ContractError::InsufficientBalance {
+                balance,
+                required: cost,
+            });
+        }
+
+        if let Some(previous) = SUBSCRIPTIONS.may_load(deps.storage, &info.sender)? {
+            SCHEDULED_DELIVERIES.remove(deps.storage, (previous.next_round, &info.sender));
+        }
+
+        SUBSCRIPTIONS.save(
+            deps.storage,
+            &info.sender,
+            &Subscription {
+                schedule,
+                gas_limit,
+                next_round: first_round,
+                deliveries: 0,
+            },
+        )?;
+        SCHEDULED_DELIVERIES.save(deps.storage, (first_round, &info.sender), &())?;
+
+        Ok(Response::new()
+            .add_attribute("action", "subscribe")
+            .add_attribute("consumer", info.sender)
+            .add_attribute("next_round", first_round.to_string())
+            .add_attribute("delivery_cost", cost))
+    }
+
+    pub fn unsubscribe(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        let subscription = SUBSCRIPTIONS
+            .may_load(deps.storage, &info.sender)?
+            .ok_or(ContractError::NoSubscription)?;
+
+        SCHEDULED_DELIVERIES.remove(deps.storage, (subscription.next_round, &info.sender));
+        SUBSCRIPTIONS.remove(deps.storage, &info.sender);
+
+        Ok(Response::new()
+            .add_attribute("action", "unsubscribe")
+            .add_attribute("consumer", info.sender))
+    }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Beacon { round } => to_json_binary(&query::beacon(deps, round)?),
         QueryMsg::LatestBeacon {} => to_json_binary(&query::latest_beacon(deps)?),
+        QueryMsg::DerivedRandomness {
+            round,
+            domain,
+            count,
+        } => to_json_binary(&query::derived_randomness(deps, round, domain, count)?),
+        QueryMsg::RoundAt { time } => to_json_binary(&query::round_at(deps, time)?),
+        QueryMsg::TimeOf { round } => to_json_binary(&query::time_of(deps, round.u64())?),
+        QueryMsg::NextRound {} => to_json_binary(&query::next_round(deps, env)?),
+        QueryMsg::Config {} => to_json_binary(&CONFIG.load(deps.storage)?),
+        QueryMsg::Subscription { consumer } => {
+            to_json_binary(&query::subscription(deps, consumer)?)
+        }
+        QueryMsg::Balance { consumer } => to_json_binary(&query::balance(deps, consumer)?),
     }
 }
 
 pub mod query {
-    use crate::msg::{BeaconResponse, ConcreteBeacon};
+    use crate::msg::{
+        BalanceResponse, BeaconResponse, ConcreteBeacon, DerivedRandomnessResponse, RoundResponse,
+    };
+    use crate::state::{Subscription, BALANCES, SUBSCRIPTIONS};
 
     use super::*;
-    use cosmwasm_std::Uint64;
+    use cosmwasm_std::{HexBinary, Timestamp, Uint128, Uint64};
 
     pub fn beacon(deps: Deps, round: Uint64) -> StdResult<BeaconResponse> {
         BEACONS
@@ -206,19 +607,93 @@ pub mod query {
                 uniform_seed: beacon.uniform_seed,
             })
     }
+
+    pub fn derived_randomness(
+        deps: Deps,
+        round: Uint64,
+        domain: String,
+        count: u32,
+    ) -> StdResult<DerivedRandomnessResponse> {
+        if count == 0 || count > derive::MAX_DERIVED_VALUES {
+            return Err(StdError::generic_err(format!(
+                "count must be between 1 and {}",
+                derive::MAX_DERIVED_VALUES
+            )));
+        }
+
+        let beacon = BEACONS.load(deps.storage, round.u64())?;
+        let values = derive::derive_randomness(&beacon.uniform_seed, &domain, count)
+            .into_iter()
+            .map(|value| HexBinary::from(value.as_slice()))
+            .collect();
+
+        Ok(DerivedRandomnessResponse {
+            round,
+            domain,
+            values,
+        })
+    }
+
+    pub fn round_at(deps: Deps, time: Timestamp) -> StdResult<RoundResponse> {
+        let network = CONFIG.load(deps.storage)?.network;
+        time_of(deps, network.round_at(time))
+    }
+
+    pub fn time_of(deps: Deps, round: u64) -> StdResult<RoundResponse> {
+        let network = CONFIG.load(deps.storage)?.network;
+        Ok(RoundResponse {
+            round: round.into(),
+            time: network.time_of(round),
+        })
+    }
+
+    pub fn next_round(deps: Deps, env: Env) -> StdResult<RoundResponse> {
+        let network = CONFIG.load(deps.storage)?.network;
+        time_of(deps, network.next_round(env.block.time))
+    }
+
+    pub fn subscription(deps: Deps, consumer: String) -> StdResult<Option<Subscription>> {
+        let consumer = deps.api.addr_validate(&consumer)?;
+        SUBSCRIPTIONS.may_load(deps.storage, &consumer)
+    }
+
+    pub fn balance(deps: Deps, consumer: String) -> StdResult<BalanceResponse> {
+        let config = CONFIG.load(deps.storage)?;
+        let consumer = deps.api.addr_validate(&consumer)?;
+
+        let balance = BALANCES
+            .may_load(deps.storage, &consumer)?
+            .unwrap_or_default();
+        let delivery_cost = SUBSCRIPTIONS
+            .may_load(deps.storage, &consumer)?
+            .map(|sub| config.delivery_cost(sub.gas_limit))
+            .unwrap_or(Uint128::zero());
+
+        Ok(BalanceResponse {
+            denom: config.fee_denom,
+            balance,
+            delivery_cost,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::msg::{BeaconResponse, ConcreteBeacon};
+    use crate::msg::{
+        BalanceResponse, BeaconResponse, ConcreteBeacon, DerivedRandomnessResponse, RoundResponse,
+        Schedule,
+    };
+    use crate::state::{Network, Randomness, Subscription};
     use cosmwasm_std::{
-        from_json,
+        coins, from_json,
         testing::{mock_dependencies, mock_env},
-        Addr, HexBinary, Uint64,
+        Addr, BankMsg, CosmosMsg, Decimal, HexBinary, OwnedDeps, Timestamp, Uint128, Uint64,
     };
     use sha2::{Digest, Sha256};
 
+    const FEE_DENOM: &str = "inj";
+
     // Real quicknet test vector (round 1000)
     const ROUND: u64 = 1000;
     const SIGNATURE_HEX: &str = "b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39";
@@ -244,6 +719,42 @@ mod tests {
         cosmwasm_std::testing::message_info(&Addr::unchecked("anyone"), &[])
     }
 
+    fn setup_contract(
+        deps: &mut OwnedDeps<
+            cosmwasm_std::testing::MockStorage,
+            cosmwasm_std::testing::MockApi,
+            cosmwasm_std::testing::MockQuerier,
+        >,
+    ) {
+        let msg = InstantiateMsg {
+            fee_denom: FEE_DENOM.to_string(),
+            base_fee: Uint128::new(100),
+            // 1 unit per 1000 gas
+            gas_price: Decimal::permille(1),
+            network: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), message_info(), msg).unwrap();
+    }
+
+    fn deposit(
+        deps: &mut OwnedDeps<
+            cosmwasm_std::testing::MockStorage,
+            cosmwasm_std::testing::MockApi,
+            cosmwasm_std::testing::MockQuerier,
+        >,
+        consumer: &Addr,
+        amount: u128,
+    ) {
+        let info = cosmwasm_std::testing::message_info(consumer, &coins(amount, FEE_DENOM));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Deposit { consumer: None },
+        )
+        .unwrap();
+    }
+
     #[test]
     fn verify_test_vector_consistency() {
         // Verify that sha256(signature) == randomness for our test vector
@@ -353,6 +864,219 @@ mod tests {
         assert_eq!(hex::encode(value.uniform_seed), RANDOMNESS_HEX);
     }
 
+    #[test]
+    fn derived_randomness_is_domain_separated() {
+        let seed: [u8; 32] = randomness_bytes().try_into().unwrap();
+
+        let spin = derive::derive_randomness(&seed, "spin", 2);
+        let mint = derive::derive_randomness(&seed, "mint", 2);
+        assert_eq!(spin.len(), 2);
+        assert_ne!(spin[0], spin[1]);
+        assert_ne!(spin[0], mint[0]);
+
+        // Deterministic and prefix-stable across counts
+        assert_eq!(derive::derive_randomness(&seed, "spin", 1)[0], spin[0]);
+
+        // Matches the documented construction byte for byte
+        let mut hasher = Sha256::new();
+        hasher.update(seed);
+        hasher.update(4u64.to_be_bytes());
+        hasher.update(b"spin");
+        hasher.update(1u32.to_be_bytes());
+        let expected: [u8; 32] = hasher.finalize().into();
+        assert_eq!(spin[1], expected);
+    }
+
+    #[test]
+    fn query_derived_randomness() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        execute(deps.as_mut(), env.clone(), message_info(), add_beacon_msg()).unwrap();
+
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::DerivedRandomness {
+                round: Uint64::new(ROUND),
+                domain: "spore-fates/splice".to_string(),
+                count: 3,
+            },
+        )
+        .unwrap();
+        let value: DerivedRandomnessResponse = from_json(&res).unwrap();
+
+        let seed: [u8; 32] = randomness_bytes().try_into().unwrap();
+        let expected = derive::derive_randomness(&seed, "spore-fates/splice", 3);
+        assert_eq!(value.values.len(), 3);
+        for (hex, bytes) in value.values.iter().zip(expected.iter()) {
+            assert_eq!(hex.as_slice(), bytes.as_slice());
+        }
+
+        // Zero or oversized counts are rejected
+        let err = query(
+            deps.as_ref(),
+            env,
+            QueryMsg::DerivedRandomness {
+                round: Uint64::new(ROUND),
+                domain: "spore-fates/splice".to_string(),
+                count: derive::MAX_DERIVED_VALUES + 1,
+            },
+        );
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn round_time_conversions() {
+        let network = Network::default();
+        let genesis = network.genesis;
+
+        // Before genesis nothing is published and nothing underflows
+        assert_eq!(network.round_at(Timestamp::from_seconds(0)), 0);
+        assert_eq!(network.next_round(Timestamp::from_seconds(0)), 1);
+        assert_eq!(network.time_of(0), genesis);
+
+        assert_eq!(network.round_at(genesis), 1);
+        assert_eq!(network.round_at(genesis.plus_seconds(2)), 1);
+        assert_eq!(network.round_at(genesis.plus_seconds(3)), 2);
+        assert_eq!(network.next_round(genesis.plus_seconds(3)), 3);
+
+        // time_of is the inverse of round_at at publication times
+        for round in [1, 2, 1000, 12_345_678] {
+            assert_eq!(network.round_at(network.time_of(round)), round);
+        }
+        assert_eq!(network.time_of(u64::MAX).nanos(), u64::MAX);
+    }
+
+    #[test]
+    fn query_rounds() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+
+        let network = Network::default();
+        let mut env = mock_env();
+        env.block.time = network.genesis.plus_seconds(3 * 999 + 1);
+
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::RoundAt {
+                time: env.block.time,
+            },
+        )
+        .unwrap();
+        let current: RoundResponse = from_json(&res).unwrap();
+        assert_eq!(current.round, Uint64::new(ROUND));
+        assert_eq!(current.time, network.genesis.plus_seconds(3 * 999));
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::NextRound {}).unwrap();
+        let next: RoundResponse = from_json(&res).unwrap();
+        assert_eq!(next.round, Uint64::new(ROUND + 1));
+        assert!(next.time > env.block.time);
+
+        let res = query(
+            deps.as_ref(),
+            env,
+            QueryMsg::TimeOf {
+                round: Uint64::new(ROUND + 1),
+            },
+        )
+        .unwrap();
+        assert_eq!(from_json::<RoundResponse>(&res).unwrap(), next);
+    }
+
+    #[test]
+    fn custom_network() {
+        let mut deps = mock_dependencies();
+        let genesis = Timestamp::from_seconds(1_000);
+        let msg = InstantiateMsg {
+            fee_denom: FEE_DENOM.to_string(),
+            base_fee: Uint128::zero(),
+            gas_price: Decimal::zero(),
+            network: Some(Network { genesis, period: 0 }),
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), message_info(), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::InvalidNetwork);
+
+        let msg = InstantiateMsg {
+            network: Some(Network {
+                genesis,
+                period: 30,
+            }),
+            ..msg
+        };
+        instantiate(deps.as_mut(), mock_env(), message_info(), msg).unwrap();
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RoundAt {
+                time: genesis.plus_seconds(95),
+            },
+        )
+        .unwrap();
+        let round: RoundResponse = from_json(&res).unwrap();
+        assert_eq!(round.round, Uint64::new(4));
+        assert_eq!(round.time, genesis.plus_seconds(90));
+    }
+
+    #[test]
+    fn migrate_writes_missing_config() {
+        let mut deps = mock_dependencies();
+        // An oracle from before subscriptions only has beacons
+        execute(deps.as_mut(), mock_env(), message_info(), add_beacon_msg()).unwrap();
+
+        let msg = MigrateMsg {
+            fee_denom: None,
+            base_fee: None,
+            gas_price: None,
+            network: None,
+        };
+        let err = migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Std(_)));
+
+        let msg = MigrateMsg {
+            fee_denom: Some(FEE_DENOM.to_string()),
+            ..msg
+        };
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+        let config: Config = from_json(&res).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                fee_denom: FEE_DENOM.to_string(),
+                base_fee: Uint128::zero(),
+                gas_price: Decimal::zero(),
+                network: Network::default(),
+            }
+        );
+        // The round queries the controller relies on work again
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::TimeOf {
+                round: Uint64::new(ROUND),
+            },
+        )
+        .unwrap();
+        let round: RoundResponse = from_json(&res).unwrap();
+        assert_eq!(round.time, Network::default().time_of(ROUND));
+
+        // An existing config is left as it is
+        let msg = MigrateMsg {
+            fee_denom: Some("other".to_string()),
+            base_fee: None,
+            gas_price: None,
+            network: None,
+        };
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        let config: Config =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(config.fee_denom, FEE_DENOM);
+    }
+
     #[test]
     fn test_idempotency() {
         let mut deps = mock_dependencies();
@@ -367,4 +1091,345 @@ mod tests {
         let status = res2.attributes.iter().find(|a| a.key == "status").unwrap();
         assert_eq!(status.value, "already_processed");
     }
+
+    #[test]
+    fn deposit_requires_fee_denom() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+        let consumer = deps.api.addr_make("consumer");
+
+        let info = cosmwasm_std::testing::message_info(&consumer, &coins(500, "uatom"));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Deposit { consumer: None },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidPayment);
+
+        deposit(&mut deps, &consumer, 500);
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Balance {
+                consumer: consumer.to_string(),
+            },
+        )
+        .unwrap();
+        let balance: BalanceResponse = from_json(&res).unwrap();
+        assert_eq!(balance.balance, Uint128::new(500));
+        assert_eq!(balance.denom, FEE_DENOM);
+    }
+
+    #[test]
+    fn subscribe_requires_balance_and_future_round() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+        let consumer = deps.api.addr_make("consumer");
+        let info = cosmwasm_std::testing::message_info(&consumer, &[]);
+
+        // 100 base + 1_000_000 gas * 0.001 = 1100
+        let subscribe = ExecuteMsg::Subscribe {
+            schedule: Schedule::Once {
+                round: Uint64::new(ROUND),
+            },
+            gas_limit: Uint64::new(1_000_000),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), subscribe.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InsufficientBalance {
+                balance: Uint128::zero(),
+                required: Uint128::new(1100),
+            }
+        );
+
+        deposit(&mut deps, &consumer, 1100);
+        execute(deps.as_mut(), mock_env(), info.clone(), subscribe).unwrap();
+
+        // Rounds that already have a beacon cannot be subscribed to
+        execute(deps.as_mut(), mock_env(), message_info(), add_beacon_msg()).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Subscribe {
+                schedule: Schedule::Once {
+                    round: Uint64::new(ROUND),
+                },
+                gas_limit: Uint64::new(1_000_000),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSchedule { .. }));
+    }
+
+    #[test]
+    fn one_off_subscription_is_delivered_and_debited() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+        let consumer = deps.api.addr_make("consumer");
+        let relayer = deps.api.addr_make("relayer");
+
+        deposit(&mut deps, &consumer, 5000);
+        let info = cosmwasm_std::testing::message_info(&consumer, &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Subscribe {
+                schedule: Schedule::Once {
+                    round: Uint64::new(ROUND),
+                },
+                gas_limit: Uint64::new(2_000_000),
+            },
+        )
+        .unwrap();
+
+        let info = cosmwasm_std::testing::message_info(&relayer, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, add_beacon_msg()).unwrap();
+
+        // Delivery with the consumer's gas limit, plus the fee payout to the relayer
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(res.messages[0].gas_limit, Some(2_000_000));
+        assert_eq!(res.messages[0].id, DELIVERY_REPLY_ID);
+        match &res.messages[1].msg {
+            CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                assert_eq!(to_address, &relayer.to_string());
+                assert_eq!(amount, &coins(2100, FEE_DENOM));
+            }
+            msg => panic!("expected bank send, got: {:?}", msg),
+        }
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Balance {
+                consumer: consumer.to_string(),
+            },
+        )
+        .unwrap();
+        let balance: BalanceResponse = from_json(&res).unwrap();
+        assert_eq!(balance.balance, Uint128::new(2900));
+
+        // One-off subscriptions are removed once delivered
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Subscription {
+                consumer: consumer.to_string(),
+            },
+        )
+        .unwrap();
+        let subscription: Option<Subscription> = from_json(&res).unwrap();
+        assert!(subscription.is_none());
+    }
+
+    #[test]
+    fn recurring_subscription_reschedules_and_lapses() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+        let consumer = deps.api.addr_make("consumer");
+
+        // Exactly one delivery worth of balance (100 + 1000 * 0.001 = 101)
+        deposit(&mut deps, &consumer, 101);
+        let info = cosmwasm_std::testing::message_info(&consumer, &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Subscribe {
+                schedule: Schedule::Every {
+                    interval: Uint64::new(ROUND),
+                },
+                gas_limit: Uint64::new(1000),
+            },
+        )
+        .unwrap();
+
+        let res = execute(deps.as_mut(), mock_env(), message_info(), add_beacon_msg()).unwrap();
+        assert_eq!(res.messages.len(), 2);
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Subscription {
+                consumer: consumer.to_string(),
+            },
+        )
+        .unwrap();
+        let subscription: Subscription = from_json::<Option<Subscription>>(&res).unwrap().unwrap();
+        assert_eq!(subscription.next_round, 2 * ROUND);
+        assert_eq!(subscription.deliveries, 1);
+    }
+
+    #[test]
+    fn unfunded_subscription_lapses() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+        let consumer = deps.api.addr_make("consumer");
+        let info = cosmwasm_std::testing::message_info(&consumer, &[]);
+
+        deposit(&mut deps, &consumer, 101);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Subscribe {
+                schedule: Schedule::Once {
+                    round: Uint64::new(ROUND),
+                },
+                gas_limit: Uint64::new(1000),
+            },
+        )
+        .unwrap();
+
+        // Drain the balance before the beacon arrives
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Withdraw { amount: None },
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        let res = execute(deps.as_mut(), mock_env(), message_info(), add_beacon_msg()).unwrap();
+        assert!(res.messages.is_empty());
+        let lapsed = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "lapsed_subscriptions")
+            .unwrap();
+        assert_eq!(lapsed.value, "1");
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Subscription {
+                consumer: consumer.to_string(),
+            },
+        )
+        .unwrap();
+        let subscription: Option<Subscription> = from_json(&res).unwrap();
+        assert!(subscription.is_none());
+    }
+
+    #[test]
+    fn deliveries_are_paginated_across_calls() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+        let relayer = deps.api.addr_make("relayer");
+
+        let subscribers = execute::MAX_DELIVERIES_PER_CALL + 5;
+        for i in 0..subscribers {
+            let consumer = deps.api.addr_make(&format!("consumer{}", i));
+            deposit(&mut deps, &consumer, 101);
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                cosmwasm_std::testing::message_info(&consumer, &[]),
+                ExecuteMsg::Subscribe {
+                    schedule: Schedule::Once {
+                        round: Uint64::new(ROUND),
+                    },
+                    gas_limit: Uint64::new(1000),
+                },
+            )
+            .unwrap();
+        }
+
+        let more_deliveries = |res: &Response| {
+            res.attributes
+                .iter()
+                .find(|a| a.key == "more_deliveries")
+                .unwrap()
+                .value
+                .clone()
+        };
+
+        // AddBeacon stores the beacon and only delivers the first batch
+        let info = cosmwasm_std::testing::message_info(&relayer, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), add_beacon_msg()).unwrap();
+        assert_eq!(
+            res.messages.len(),
+            execute::MAX_DELIVERIES_PER_CALL as usize + 1
+        );
+        assert_eq!(more_deliveries(&res), "true");
+
+        let deliver = ExecuteMsg::Deliver {
+            round: Uint64::new(ROUND),
+            limit: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), deliver.clone()).unwrap();
+        assert_eq!(res.messages.len(), 6);
+        assert_eq!(more_deliveries(&res), "false");
+        match &res.messages[5].msg {
+            CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                assert_eq!(to_address, &relayer.to_string());
+                assert_eq!(amount, &coins(5 * 101, FEE_DENOM));
+            }
+            msg => panic!("expected bank send, got: {:?}", msg),
+        }
+
+        // Nothing left to deliver
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), deliver).unwrap();
+        assert!(res.messages.is_empty());
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Deliver {
+                round: Uint64::new(ROUND + 1),
+                limit: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoBeacon { round: ROUND + 1 });
+    }
+
+    #[test]
+    fn recurring_subscription_skips_stored_rounds() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+        let consumer = deps.api.addr_make("consumer");
+        let stored = Randomness {
+            uniform_seed: [0; 32],
+        };
+
+        // Round 500 is already stored, so the first delivery is round 1000
+        BEACONS.save(deps.as_mut().storage, 500, &stored).unwrap();
+        deposit(&mut deps, &consumer, 1000);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            cosmwasm_std::testing::message_info(&consumer, &[]),
+            ExecuteMsg::Subscribe {
+                schedule: Schedule::Every {
+                    interval: Uint64::new(500),
+                },
+                gas_limit: Uint64::new(1000),
+            },
+        )
+        .unwrap();
+
+        // Round 1500 is relayed before round 1000
+        BEACONS.save(deps.as_mut().storage, 1500, &stored).unwrap();
+        execute(deps.as_mut(), mock_env(), message_info(), add_beacon_msg()).unwrap();
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Subscription {
+                consumer: consumer.to_string(),
+            },
+        )
+        .unwrap();
+        let subscription: Subscription = from_json::<Option<Subscription>>(&res).unwrap().unwrap();
+        assert_eq!(subscription.next_round, 2000);
+        assert_eq!(subscription.deliveries, 1);
+    }
 }
@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{OverflowError, StdError, Uint128};
 use thiserror::Error;
 
 #[derive(Debug, Error, PartialEq)]
@@ -9,6 +9,30 @@ pub enum ContractError {
     #[error("Invalid randomness")]
     InvalidRandomness,
 
+    #[error("Invalid payment")]
+    InvalidPayment,
+
+    #[error("Insufficient balance: {balance} available, {required} required")]
+    InsufficientBalance { balance: Uint128, required: Uint128 },
+
+    #[error("Invalid schedule: {msg}")]
+    InvalidSchedule { msg: String },
+
+    #[error("Gas limit must be between 1 and {max}")]
+    InvalidGasLimit { max: u64 },
+
+    #[error("Network period must be at least one second")]
+    InvalidNetwork,
+
+    #[error("No beacon stored for round {round}")]
+    NoBeacon { round: u64 },
+
+    #[error("No subscription")]
+    NoSubscription,
+
+    #[error(transparent)]
+    Overflow(#[from] OverflowError),
+
     #[error(transparent)]
     Std(#[from] StdError),
 }
@@ -1,8 +1,34 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{HexBinary, Uint64};
+use cosmwasm_std::{Decimal, HexBinary, Timestamp, Uint128, Uint64};
+
+use crate::state::{Config, Network, Subscription};
 
 #[cw_serde]
-pub struct InstantiateMsg {}
+pub struct InstantiateMsg {
+    pub fee_denom: String,
+    pub base_fee: Uint128,
+    pub gas_price: Decimal,
+    /// Defaults to drand quicknet
+    pub network: Option<Network>,
+}
+
+/// Oracles from before prepaid subscriptions have no config; `fee_denom` is
+/// required to migrate those. Fees default to zero and the network to quicknet.
+#[cw_serde]
+pub struct MigrateMsg {
+    pub fee_denom: Option<String>,
+    pub base_fee: Option<Uint128>,
+    pub gas_price: Option<Decimal>,
+    pub network: Option<Network>,
+}
+
+#[cw_serde]
+pub enum Schedule {
+    /// Deliver a single beacon for the given future round
+    Once { round: Uint64 },
+    /// Deliver every beacon whose round is a multiple of `interval`
+    Every { interval: Uint64 },
+}
 
 #[cw_serde]
 pub enum ExecuteMsg {
@@ -12,6 +38,26 @@ pub enum ExecuteMsg {
         randomness: HexBinary,
     },
     NextBeacon,
+    /// Top up the prepaid delivery balance of `consumer` (defaults to the sender)
+    Deposit {
+        consumer: Option<String>,
+    },
+    /// Withdraw unused prepaid balance (defaults to the full balance)
+    Withdraw {
+        amount: Option<Uint128>,
+    },
+    /// Replace the sender's subscription. Each delivery is debited from the prepaid balance.
+    Subscribe {
+        schedule: Schedule,
+        gas_limit: Uint64,
+    },
+    Unsubscribe {},
+    /// Deliver up to `limit` paid subscriptions for a stored round that `AddBeacon`
+    /// did not get to. Collected fees are paid to the sender.
+    Deliver {
+        round: Uint64,
+        limit: Option<u32>,
+    },
 }
 
 #[cw_serde]
@@ -24,6 +70,43 @@ pub enum QueryMsg {
     // Get the latest beacon known to the contract
     #[returns(ConcreteBeacon)]
     LatestBeacon {},
+
+    /// Derive `count` domain-separated values from a stored beacon.
+    /// See `contract::derive::derive_randomness` for the exact construction.
+    #[returns(DerivedRandomnessResponse)]
+    DerivedRandomness {
+        round: Uint64,
+        domain: String,
+        count: u32,
+    },
+
+    /// Latest round published at `time` (0 before genesis)
+    #[returns(RoundResponse)]
+    RoundAt { time: Timestamp },
+
+    /// Time at which `round` is published
+    #[returns(RoundResponse)]
+    TimeOf { round: Uint64 },
+
+    /// First round that is not yet public at the current block time
+    #[returns(RoundResponse)]
+    NextRound {},
+
+    #[returns(Config)]
+    Config {},
+
+    #[returns(Option<Subscription>)]
+    Subscription { consumer: String },
+
+    #[returns(BalanceResponse)]
+    Balance { consumer: String },
+}
+
+#[cw_serde]
+pub struct RoundResponse {
+    pub round: Uint64,
+    /// Publication time of `round`
+    pub time: Timestamp,
 }
 
 #[cw_serde]
@@ -36,3 +119,18 @@ pub struct ConcreteBeacon {
     pub round: Uint64,
     pub uniform_seed: [u8; 32],
 }
+
+#[cw_serde]
+pub struct DerivedRandomnessResponse {
+    pub round: Uint64,
+    pub domain: String,
+    pub values: Vec<HexBinary>,
+}
+
+#[cw_serde]
+pub struct BalanceResponse {
+    pub denom: String,
+    pub balance: Uint128,
+    /// Cost of a single delivery at the consumer's current gas limit (zero without a subscription)
+    pub delivery_cost: Uint128,
+}
@@ -1,11 +1,81 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::Addr;
-use cw_storage_plus::Map;
+use cosmwasm_std::{Addr, Decimal, Timestamp, Uint128};
+use cw_storage_plus::{Item, Map};
 use std::collections::HashSet;
 
+use crate::msg::Schedule;
+
+pub const CONFIG: Item<Config> = Item::new("config");
 pub const BEACONS: Map<u64, Randomness> = Map::new("beacons");
 pub const DELIVERY_QUEUES: Map<u64, DeliveryQueue> = Map::new("delivery_queues");
 
+// Prepaid delivery balances, denominated in `Config::fee_denom`
+pub const BALANCES: Map<&Addr, Uint128> = Map::new("balances");
+pub const SUBSCRIPTIONS: Map<&Addr, Subscription> = Map::new("subscriptions");
+// (round, consumer) index of paid deliveries waiting for a beacon
+pub const SCHEDULED_DELIVERIES: Map<(u64, &Addr), ()> = Map::new("scheduled_deliveries");
+
+#[cw_serde]
+pub struct Config {
+    pub fee_denom: String,
+    /// Flat fee debited for every paid delivery
+    pub base_fee: Uint128,
+    /// Fee per unit of the consumer's chosen gas limit
+    pub gas_price: Decimal,
+    /// Round timing of the drand network being relayed (quicknet for older configs)
+    #[serde(default)]
+    pub network: Network,
+}
+
+impl Config {
+    pub fn delivery_cost(&self, gas_limit: u64) -> Uint128 {
+        self.base_fee + Uint128::from(gas_limit).mul_ceil(self.gas_price)
+    }
+}
+
+/// Timing parameters of a drand network: round 1 is published at `genesis`,
+/// and every following round `period` seconds later.
+#[cw_serde]
+pub struct Network {
+    pub genesis: Timestamp,
+    pub period: u64,
+}
+
+impl Default for Network {
+    /// drand quicknet
+    fn default() -> Self {
+        Network {
+            genesis: Timestamp::from_seconds(1692803367),
+            period: 3,
+        }
+    }
+}
+
+impl Network {
+    /// Latest round published at `time`, or 0 before genesis
+    pub fn round_at(&self, time: Timestamp) -> u64 {
+        if time < self.genesis {
+            return 0;
+        }
+        let since_genesis = time.seconds() - self.genesis.seconds();
+        since_genesis / self.period + 1
+    }
+
+    /// Publication time of `round`. Round 0 is treated as genesis.
+    pub fn time_of(&self, round: u64) -> Timestamp {
+        let offset = round
+            .saturating_sub(1)
+            .saturating_mul(self.period)
+            .saturating_mul(1_000_000_000);
+        Timestamp::from_nanos(self.genesis.nanos().saturating_add(offset))
+    }
+
+    /// First round whose beacon is not yet public at `time`
+    pub fn next_round(&self, time: Timestamp) -> u64 {
+        self.round_at(time) + 1
+    }
+}
+
 #[cw_serde]
 pub struct Randomness {
     pub uniform_seed: [u8; 32],
@@ -16,3 +86,11 @@ pub struct Randomness {
 pub struct DeliveryQueue {
     pub receivers: HashSet<Addr>,
 }
+
+#[cw_serde]
+pub struct Subscription {
+    pub schedule: Schedule,
+    pub gas_limit: u64,
+    pub next_round: u64,
+    pub deliveries: u64,
+}
@@ -2,7 +2,7 @@ use cosmwasm_std::{entry_point, to_json_binary, Binary, Deps, DepsMut, Env, Mess
 use cw721::msg::NftExtensionMsg;
 use cw721_base::traits::{Cw721Execute, Cw721Query};
 use cw721_metadata_onchain::Cw721MetadataContract;
-use spore_fates::cw721::{ExecuteMsg, TraitExtension};
+use spore_fates::cw721::{ExecuteMsg, TraitExtension, MAX_SUBSTRATE};
 
 pub mod error;
 pub mod msg;
@@ -181,7 +181,7 @@ fn execute_update_traits(
             trait_name: "spores".to_string(),
         });
     }
-    if traits.substrate > 4 {
+    if traits.substrate > MAX_SUBSTRATE {
         return Err(ContractError::InvalidTrait {
             trait_name: "substrate".to_string(),
         });
@@ -383,6 +383,7 @@ mod tests {
                 base_cap: 0,
                 base_stem: 0,
                 base_spores: 0,
+                generation: 0,
             },
         };
         // Minter must sign mint message
@@ -445,6 +446,7 @@ mod tests {
             base_cap: 0,
             base_stem: 0,
             base_spores: 0,
+            generation: 0,
         };
 
         let msg = ExecuteMsg::UpdateTraits {
@@ -486,6 +488,42 @@ mod tests {
         assert_eq!(get_trait_val("substrate"), "1");
     }
 
+    #[test]
+    fn test_update_traits_overmind() {
+        let mut deps = mock_dependencies();
+        let minter = deps.api.addr_make("minter");
+        let user = deps.api.addr_make("user");
+
+        setup_contract(deps.as_mut(), &minter).unwrap();
+        mint_token(deps.as_mut(), &minter, "1", &user).unwrap();
+
+        // An Overmind keeps spinning and harvesting like any other tier
+        let overmind = TraitExtension {
+            cap: 3,
+            stem: 3,
+            spores: 3,
+            substrate: MAX_SUBSTRATE,
+            genes: vec![4, 4, 4, 4, 4, 4, 4, 4],
+            base_cap: 0,
+            base_stem: 0,
+            base_spores: 0,
+            generation: 0,
+        };
+        let msg = ExecuteMsg::UpdateTraits {
+            token_id: "1".to_string(),
+            traits: overmind,
+        };
+        let info = message_info(&minter, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let substrate = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "substrate")
+            .unwrap();
+        assert_eq!(substrate.value, "5");
+    }
+
     #[test]
     fn test_update_traits_unauthorized() {
         let mut deps = mock_dependencies();
@@ -504,6 +542,7 @@ mod tests {
             base_cap: 0,
             base_stem: 0,
             base_spores: 0,
+            generation: 0,
         };
 
         let msg = ExecuteMsg::UpdateTraits {
@@ -537,6 +576,7 @@ mod tests {
             base_cap: 0,
             base_stem: 0,
             base_spores: 0,
+            generation: 0,
         };
 
         let msg = ExecuteMsg::UpdateTraits {
@@ -553,11 +593,12 @@ mod tests {
             cap: 0,
             stem: 0,
             spores: 0,
-            substrate: 5, // Invalid: max is 4
+            substrate: 6, // Invalid: max is 5 (Overmind)
             genes: vec![0, 0, 0, 0, 0, 0, 0, 0],
             base_cap: 0,
             base_stem: 0,
             base_spores: 0,
+            generation: 0,
         };
 
         let msg = ExecuteMsg::UpdateTraits {
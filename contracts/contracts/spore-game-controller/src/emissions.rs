@@ -0,0 +1,170 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{StdResult, Timestamp, Uint128};
+
+/// Halvings after which any rate has reached zero
+const MAX_HALVINGS: u64 = 128;
+
+/// Time-based rewards paid out of a funded reserve
+#[cw_serde]
+pub struct EmissionSchedule {
+    pub rate_per_second: Uint128,
+    /// Seconds between halvings of the rate; `None` keeps it constant
+    pub halving_interval: Option<u64>,
+    /// Start of the first epoch
+    pub start: Timestamp,
+}
+
+impl EmissionSchedule {
+    pub fn rate_at(&self, time: Timestamp) -> Uint128 {
+        match self.halving_interval {
+            Some(interval) if time > self.start => {
+                let epoch = (time.seconds() - self.start.seconds()) / interval;
+                if epoch >= MAX_HALVINGS {
+                    Uint128::zero()
+                } else {
+                    self.rate_per_second >> (epoch as u32)
+                }
+            }
+            _ => self.rate_per_second,
+        }
+    }
+
+    /// Amount the schedule releases over `[from, to)`, ignoring the reserve
+    pub fn emitted_between(&self, from: Timestamp, to: Timestamp) -> Uint128 {
+        let start = self.start.seconds();
+        let mut t = from.seconds().max(start);
+        let to = to.seconds();
+
+        let Some(interval) = self.halving_interval else {
+            return self
+                .rate_per_second
+                .saturating_mul(Uint128::from(to.saturating_sub(t)));
+        };
+
+        let mut total = Uint128::zero();
+        while t < to {
+            let epoch = (t - start) / interval;
+            if epoch >= MAX_HALVINGS {
+                break;
+            }
+            let epoch_end = start
+                .saturating_add((epoch + 1).saturating_mul(interval))
+                .min(to);
+            let rate = self.rate_per_second >> (epoch as u32);
+            total = total.saturating_add(rate.saturating_mul(Uint128::from(epoch_end - t)));
+            t = epoch_end;
+        }
+        total
+    }
+}
+
+#[cw_serde]
+pub struct Emissions {
+    pub schedule: EmissionSchedule,
+    /// Funded and not yet emitted
+    pub reserve: Uint128,
+    pub total_emitted: Uint128,
+    /// Emissions up to this time are in the reward index
+    pub last_accrued: Timestamp,
+}
+
+impl Emissions {
+    /// No emissions until the admin sets a rate and someone funds the reserve
+    pub fn new(now: Timestamp) -> Self {
+        Emissions {
+            schedule: EmissionSchedule {
+                rate_per_second: Uint128::zero(),
+                halving_interval: None,
+                start: now,
+            },
+            reserve: Uint128::zero(),
+            total_emitted: Uint128::zero(),
+            last_accrued: now,
+        }
+    }
+
+    /// Release what the schedule emitted since the last accrual, capped by the
+    /// reserve. Nothing is emitted while nothing is staked.
+    pub fn accrue(&mut self, total_shares: Uint128, now: Timestamp) -> StdResult<Uint128> {
+        if now <= self.last_accrued {
+            return Ok(Uint128::zero());
+        }
+        let released = if total_shares.is_zero() {
+            Uint128::zero()
+        } else {
+            self.schedule
+                .emitted_between(self.last_accrued, now)
+                .min(self.reserve)
+        };
+
+        self.reserve -= released;
+        self.total_emitted = self.total_emitted.checked_add(released)?;
+        self.last_accrued = now;
+        Ok(released)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: u64) -> Timestamp {
+        Timestamp::from_seconds(seconds)
+    }
+
+    #[test]
+    fn halving_epochs() {
+        let schedule = EmissionSchedule {
+            rate_per_second: Uint128::new(100),
+            halving_interval: Some(10),
+            start: at(1_000),
+        };
+        assert_eq!(schedule.rate_at(at(1_009)), Uint128::new(100));
+        assert_eq!(schedule.rate_at(at(1_010)), Uint128::new(50));
+        assert_eq!(schedule.rate_at(at(1_025)), Uint128::new(25));
+
+        // Nothing before the start, then 10s at 100 + 10s at 50 + 5s at 25
+        assert_eq!(
+            schedule.emitted_between(at(900), at(1_025)),
+            Uint128::new(1_000 + 500 + 125)
+        );
+        assert_eq!(
+            schedule.emitted_between(at(1_005), at(1_012)),
+            Uint128::new(500 + 100)
+        );
+        // The rate runs out eventually
+        assert_eq!(schedule.rate_at(at(1_000 + 10 * 200)), Uint128::zero());
+    }
+
+    #[test]
+    fn accrue_is_capped_by_reserve() {
+        let mut emissions = Emissions::new(at(0));
+        emissions.schedule.rate_per_second = Uint128::new(10);
+        emissions.reserve = Uint128::new(1_005);
+
+        let released = emissions.accrue(Uint128::new(100), at(30)).unwrap();
+        assert_eq!(released, Uint128::new(300));
+        assert_eq!(emissions.reserve, Uint128::new(705));
+
+        // Far in the future only the reserve is left to give
+        let released = emissions.accrue(Uint128::new(100), at(1_000)).unwrap();
+        assert_eq!(released, Uint128::new(705));
+        assert!(emissions.reserve.is_zero());
+        assert_eq!(emissions.total_emitted, Uint128::new(1_005));
+    }
+
+    #[test]
+    fn accrue_skips_empty_pool() {
+        let mut emissions = Emissions::new(at(0));
+        emissions.schedule.rate_per_second = Uint128::new(1);
+        emissions.reserve = Uint128::new(1_000);
+
+        // Nobody staked: the time passes without emitting
+        assert!(emissions.accrue(Uint128::zero(), at(50)).unwrap().is_zero());
+        assert_eq!(emissions.last_accrued, at(50));
+        assert_eq!(emissions.reserve, Uint128::new(1_000));
+
+        let released = emissions.accrue(Uint128::new(100), at(60)).unwrap();
+        assert_eq!(released, Uint128::new(10));
+    }
+}
@@ -27,10 +27,13 @@ pub enum ContractError {
     #[error("No pending spin")]
     NoPendingSpin {},
 
+    #[error("Token {token_id} is not in the game")]
+    TokenNotFound { token_id: String },
+
     #[error("Claimed")]
     Claimed {},
 
-    #[error("Already at max substrate level")]
+    #[error("Ascension stops at substrate 4; the Overmind only comes from splicing")]
     MaxSubstrate {},
 
     #[error("Already at max substrate level")]
@@ -53,4 +56,34 @@ pub enum ContractError {
 
     #[error("No pending ascend")]
     NoPendingAscend {},
+
+    #[error("Invalid pricing curve: {msg}")]
+    InvalidPricingCurve { msg: String },
+
+    #[error("Minimum round delay must be at least 1")]
+    InvalidRoundDelay {},
+
+    #[error("Drand round {round} was already public when the request was made")]
+    RoundAlreadyPublished { round: u64 },
+
+    #[error("Invalid fee split: {msg}")]
+    InvalidFeeSplit { msg: String },
+
+    #[error("Halving interval must be at least 1 second")]
+    InvalidEmissionSchedule {},
+
+    #[error("Epoch length must be at least 1 second")]
+    InvalidEpochLength {},
+
+    #[error("History retention must be at least 1 epoch")]
+    InvalidHistoryRetention {},
+
+    #[error("Invalid game rules: {msg}")]
+    InvalidGameRules { msg: String },
+
+    #[error("Gene slot {slot} doesn't exist")]
+    InvalidGeneSlot { slot: u8 },
+
+    #[error("No pending gene edit")]
+    NoPendingGeneEdit {},
 }
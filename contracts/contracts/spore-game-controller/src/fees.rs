@@ -0,0 +1,118 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Uint128, Uint256};
+
+use crate::error::ContractError;
+use crate::rules::BPS;
+
+/// How resolved mint and spin payments are divided. The three shares must add
+/// up to 100%; rounding dust always goes to the reward pool.
+#[cw_serde]
+pub struct FeeSplit {
+    pub treasury_bps: u16,
+    pub burn_bps: u16,
+    pub reward_bps: u16,
+}
+
+impl Default for FeeSplit {
+    /// Everything to the reward pool, as before fees existed
+    fn default() -> Self {
+        FeeSplit {
+            treasury_bps: 0,
+            burn_bps: 0,
+            reward_bps: BPS,
+        }
+    }
+}
+
+/// One payment after the split
+#[derive(Debug, PartialEq)]
+pub struct Fees {
+    pub treasury: Uint128,
+    pub burn: Uint128,
+    pub rewards: Uint128,
+}
+
+impl FeeSplit {
+    pub fn validate(&self, has_burn_addr: bool) -> Result<(), ContractError> {
+        let total = self.treasury_bps as u32 + self.burn_bps as u32 + self.reward_bps as u32;
+        if total != BPS as u32 {
+            return Err(ContractError::InvalidFeeSplit {
+                msg: format!("shares add up to {} bps instead of {}", total, BPS),
+            });
+        }
+        if self.burn_bps > 0 && !has_burn_addr {
+            return Err(ContractError::InvalidFeeSplit {
+                msg: "a burn share needs a burn address".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn split(&self, amount: Uint128) -> Fees {
+        let share = |bps: u16| {
+            let part = Uint256::from(amount) * Uint256::from(bps) / Uint256::from(BPS);
+            // At most `amount`, so always fits
+            Uint128::try_from(part).unwrap()
+        };
+        let treasury = share(self.treasury_bps);
+        let burn = share(self.burn_bps);
+        Fees {
+            treasury,
+            burn,
+            rewards: amount - treasury - burn,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_sends_dust_to_rewards() {
+        let split = FeeSplit {
+            treasury_bps: 1_000,
+            burn_bps: 500,
+            reward_bps: 8_500,
+        };
+        assert_eq!(
+            split.split(Uint128::new(1_000_000)),
+            Fees {
+                treasury: Uint128::new(100_000),
+                burn: Uint128::new(50_000),
+                rewards: Uint128::new(850_000),
+            }
+        );
+        assert_eq!(
+            split.split(Uint128::new(19)),
+            Fees {
+                treasury: Uint128::new(1),
+                burn: Uint128::zero(),
+                rewards: Uint128::new(18),
+            }
+        );
+        assert_eq!(
+            FeeSplit::default().split(Uint128::new(7)).rewards,
+            Uint128::new(7)
+        );
+    }
+
+    #[test]
+    fn validate_checks_total_and_burn_addr() {
+        let split = FeeSplit {
+            treasury_bps: 1_000,
+            burn_bps: 0,
+            reward_bps: 8_000,
+        };
+        assert!(split.validate(false).is_err());
+
+        let split = FeeSplit {
+            treasury_bps: 1_000,
+            burn_bps: 1_000,
+            reward_bps: 8_000,
+        };
+        assert!(split.validate(false).is_err());
+        split.validate(true).unwrap();
+        FeeSplit::default().validate(false).unwrap();
+    }
+}
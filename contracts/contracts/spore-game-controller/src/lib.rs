@@ -1,39 +1,122 @@
-use std::str::FromStr;
-
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
-    entry_point, to_json_binary, Addr, BankMsg, Binary, Coin, Decimal, Deps, DepsMut, Empty, Env,
-    MessageInfo, Response, StdResult, Uint128, Uint64, WasmMsg,
+    entry_point, from_json, to_json_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Decimal,
+    Decimal256, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, SignedDecimal, StdError,
+    StdResult, Storage, Timestamp, Uint128, Uint256, Uint64, WasmMsg,
 };
 use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Denom};
 use cw721::msg::NftExtensionMsg;
+use cw_storage_plus::Bound;
 use sha2::{Digest, Sha256};
 use spore_fates::cw721::TraitExtension;
 use spore_fates::game::GlobalBiomass;
+use std::collections::BTreeMap;
 
+pub mod emissions;
 pub mod error;
+pub mod fees;
+pub mod lineage;
 pub mod msg;
+pub mod pricing;
+pub mod rules;
+pub mod spin;
+pub mod splice;
 pub mod state;
+pub mod twap;
 
+use crate::emissions::{EmissionSchedule, Emissions};
 use crate::error::ContractError;
+use crate::fees::{FeeSplit, Fees};
+use crate::lineage::{DEFAULT_LINEAGE_DEPTH, MAX_LINEAGE_DEPTH};
 use crate::msg::{
-    BeaconResponse, EcosystemMetricsResponse, ExecuteMsg, GameStatsResponse, InstantiateMsg,
-    LeaderboardResponse, MintPriceResponse, OracleQueryMsg, PendingAscendResponse,
-    PendingMintResponse, PendingRewardsResponse, PendingSpinResponse, PendingSpliceResponse,
-    PlayerProfileResponse, QueryMsg, TraitTarget,
+    AccruedFeesResponse, AscendSimulationResponse, BeaconResponse, DescendantsResponse,
+    EcosystemHistoryResponse, EcosystemMetricsResponse, EmissionScheduleResponse, ExecuteMsg,
+    GameStatsResponse, InstantiateMsg, LeaderboardResponse, LineageResponse, MigrateMsg,
+    MintPriceResponse, MintQuoteResponse, OracleQueryMsg, PendingAscendResponse,
+    PendingGeneEditResponse, PendingMintResponse, PendingRewardsResponse, PendingSpinResponse,
+    PendingSpliceResponse, PlayerProfileResponse, QueryMsg, ReceiveMsg, RoundResponse,
+    ShareEstimate, SpinSimulationResponse, SplicePreviewResponse, SubstrateOdds, SunLineResponse,
+    TraitTarget, TraitValueOdds,
+};
+use crate::pricing::{PricingCurve, MAX_QUOTE_QUANTITY};
+use crate::rules::{GameRules, BPS};
+use crate::spin::{set_trait_value, spin_result, trait_value};
+use crate::splice::{
+    inherit_substrate, preview_splice, reroll_gene, slot_outcome, SlotOutcome, GENE_SLOTS,
 };
 use crate::state::{
-    GameConfig, GameStats, GlobalState, LeaderboardEntry, PendingAscend, PendingMint, PendingSpin,
-    PendingSplice, PlayerInfo, TokenInfo, BIOMASS, CONFIG, GAME_STATS, GLOBAL_STATE, LEADERBOARD,
-    LOCKED_TOKENS, MINT_COUNTER, PENDING_ASCENDS, PENDING_MINTS, PENDING_SPINS, PENDING_SPLICES,
-    PLAYER_INFO, TOKEN_INFO,
+    CanopyEpoch, EcosystemSnapshot, GameConfig, GameStats, GlobalState, LeaderboardEntry,
+    PendingAscend, PendingGeneEdit, PendingMint, PendingSpin, PendingSplice, PlayerInfo, TokenInfo,
+    BIOMASS, BIOMASS_TWAP, CANOPY_EPOCH, CONFIG, ECOSYSTEM_HISTORY, EMISSIONS, GAME_STATS,
+    GLOBAL_STATE, LEADERBOARD, LEGACY_CONFIG, LOCKED_TOKENS, MINT_COUNTER, PENDING_ASCENDS,
+    PENDING_GENE_EDITS, PENDING_MINTS, PENDING_SPINS, PENDING_SPLICES, PLAYER_INFO, TOKEN_INFO,
+    TREASURY,
 };
+use crate::twap::BiomassTwap;
 
 const CONTRACT_NAME: &str = "crates.io:spore-game-controller";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-const DRAND_GENESIS: u64 = 1692803367;
-const DRAND_PERIOD: u64 = 3;
+// One round of margin beyond the next one (~6s on quicknet)
+const DEFAULT_MIN_ROUND_DELAY: u64 = 2;
+
+// Sun Line snapshot interval (one day)
+const DEFAULT_EPOCH_LENGTH: u64 = 86_400;
+
+// A month of daily snapshots
+const DEFAULT_HISTORY_RETENTION: u64 = 30;
+
+// Pagination for EcosystemHistory
+const DEFAULT_HISTORY_LIMIT: u32 = 30;
+const MAX_HISTORY_LIMIT: u32 = 100;
+
+// Highest substrate ascension reaches. Level 5, the Overmind, is exclusive to
+// splicing two level 4s.
+const MAX_ASCEND_SUBSTRATE: u8 = 4;
+
+/// Drand round a new request commits to. The target stays unpublished even if the
+/// request's block time lags real time by up to `max_block_time` seconds, and
+/// `min_round_delay` rounds lie between it and the latest public round.
+fn target_drand_round(deps: Deps, env: &Env, config: &GameConfig) -> StdResult<u64> {
+    let latest: RoundResponse = deps.querier.query_wasm_smart(
+        config.oracle_addr.to_string(),
+        &OracleQueryMsg::RoundAt {
+            time: env.block.time.plus_seconds(config.max_block_time),
+        },
+    )?;
+    Ok(latest.round.u64() + config.min_round_delay)
+}
+
+/// Fetch the beacon a pending request committed to. Rounds that were already public
+/// when the request was made are rejected, since their outcome was knowable up front.
+/// Requests from before `requested_at` was recorded can't be checked and resolve as
+/// they used to.
+fn fetch_beacon(
+    deps: Deps,
+    config: &GameConfig,
+    target_round: u64,
+    requested_at: Timestamp,
+) -> Result<BeaconResponse, ContractError> {
+    let published: RoundResponse = deps.querier.query_wasm_smart(
+        config.oracle_addr.to_string(),
+        &OracleQueryMsg::TimeOf {
+            round: Uint64::from(target_round),
+        },
+    )?;
+    if requested_at != Timestamp::default() && published.time <= requested_at {
+        return Err(ContractError::RoundAlreadyPublished {
+            round: target_round,
+        });
+    }
+
+    Ok(deps.querier.query_wasm_smart(
+        config.oracle_addr.to_string(),
+        &OracleQueryMsg::Beacon {
+            round: Uint64::from(target_round),
+        },
+    )?)
+}
 
 fn parse_traits(extension: NftExtensionMsg) -> TraitExtension {
     let attributes = extension.attributes.unwrap_or_default();
@@ -82,33 +165,72 @@ fn parse_traits(extension: NftExtensionMsg) -> TraitExtension {
         base_cap: get_val("base_cap").parse().unwrap_or(0),
         base_stem: get_val("base_stem").parse().unwrap_or(0),
         base_spores: get_val("base_spores").parse().unwrap_or(0),
+        generation: get_val("generation").parse().unwrap_or(0),
     }
 }
 
 #[entry_point]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
+    env: Env,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     MINT_COUNTER.save(deps.storage, &1u64)?;
 
+    msg.mint_pricing.validate()?;
+
+    let min_round_delay = msg.min_round_delay.unwrap_or(DEFAULT_MIN_ROUND_DELAY);
+    if min_round_delay == 0 {
+        return Err(ContractError::InvalidRoundDelay {});
+    }
+
+    let burn_addr = msg
+        .burn_addr
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let fee_split = msg.fee_split.unwrap_or_default();
+    fee_split.validate(burn_addr.is_some())?;
+
+    let admin = match msg.admin {
+        Some(admin) => deps.api.addr_validate(&admin)?,
+        None => info.sender,
+    };
+
+    let epoch_length = msg.epoch_length.unwrap_or(DEFAULT_EPOCH_LENGTH);
+    if epoch_length == 0 {
+        return Err(ContractError::InvalidEpochLength {});
+    }
+    let history_retention = msg.history_retention.unwrap_or(DEFAULT_HISTORY_RETENTION);
+    if history_retention == 0 {
+        return Err(ContractError::InvalidHistoryRetention {});
+    }
+    let rules = msg.rules.unwrap_or_default();
+    rules.validate()?;
+
     let config = GameConfig {
-        payment_denom: msg.payment_denom,
+        admin,
+        payment: msg.payment.into_checked(deps.as_ref())?,
         spin_cost: msg.spin_cost,
-        mint_cost: msg.mint_cost,
-        mint_cost_increment: msg.mint_cost_increment,
+        mint_pricing: msg.mint_pricing,
         oracle_addr: deps.api.addr_validate(&msg.oracle_addr)?,
         cw721_addr: deps.api.addr_validate(&msg.cw721_addr)?,
+        min_round_delay,
+        max_block_time: msg.max_block_time.unwrap_or_default(),
+        fee_split,
+        burn_addr,
+        epoch_length,
+        history_retention,
+        rules,
     };
 
     let global_state = GlobalState {
         total_shares: Uint128::zero(),
-        global_reward_index: Uint128::zero(),
+        global_reward_index: Decimal256::zero(),
         spin_nonce: 0u64,
+        undistributed_dust: Uint128::zero(),
     };
 
     let biomass = GlobalBiomass {
@@ -123,11 +245,86 @@ pub fn instantiate(
     CONFIG.save(deps.storage, &config)?;
     GLOBAL_STATE.save(deps.storage, &global_state)?;
     BIOMASS.save(deps.storage, &biomass)?;
+    BIOMASS_TWAP.save(deps.storage, &BiomassTwap::new(env.block.time))?;
     LEADERBOARD.save(deps.storage, &vec![])?;
+    TREASURY.save(deps.storage, &Default::default())?;
+    EMISSIONS.save(deps.storage, &Emissions::new(env.block.time))?;
+    let epoch = CanopyEpoch {
+        epoch: 0,
+        started_at: env.block.time,
+        sun_line: Decimal::zero(),
+        active_mushrooms: 0,
+    };
+    CANOPY_EPOCH.save(deps.storage, &epoch)?;
+    record_ecosystem_snapshot(deps.storage, &epoch)?;
+
+    let payment = match &config.payment {
+        Denom::Native(denom) => denom.to_string(),
+        Denom::Cw20(token) => token.to_string(),
+    };
 
     Ok(Response::new()
         .add_attribute("action", "instantiate")
-        .add_attribute("payment_denom", config.payment_denom))
+        .add_attribute("payment", payment))
+}
+
+/// Upgrades state written by earlier releases. Outstanding requests stay readable
+/// and resolve without the published round check.
+#[entry_point]
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    // First-release config: plain denom, linear mint price and no admin
+    if CONFIG.load(deps.storage).is_err() {
+        let legacy = LEGACY_CONFIG.load(deps.storage)?;
+        let admin = msg
+            .admin
+            .ok_or_else(|| StdError::generic_err("admin is required to migrate this config"))?;
+        let config = GameConfig {
+            admin: deps.api.addr_validate(&admin)?,
+            payment: Denom::Native(legacy.payment_denom),
+            spin_cost: legacy.spin_cost,
+            mint_pricing: PricingCurve::Linear {
+                base: legacy.mint_cost,
+                increment: legacy.mint_cost_increment,
+            },
+            cw721_addr: legacy.cw721_addr,
+            oracle_addr: legacy.oracle_addr,
+            min_round_delay: DEFAULT_MIN_ROUND_DELAY,
+            max_block_time: 0,
+            fee_split: Default::default(),
+            burn_addr: None,
+            epoch_length: DEFAULT_EPOCH_LENGTH,
+            history_retention: DEFAULT_HISTORY_RETENTION,
+            rules: Default::default(),
+        };
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    // The reward index used to be a Uint128. Decimal256 reads those values as the
+    // same number and `reward_debt` keeps its meaning (`shares * index`), so the
+    // TOKEN_INFO records stay valid as stored and every pending balance is unchanged.
+    let global_state = GLOBAL_STATE.load(deps.storage)?;
+    GLOBAL_STATE.save(deps.storage, &global_state)?;
+
+    if !TREASURY.exists(deps.storage) {
+        TREASURY.save(deps.storage, &Default::default())?;
+    }
+    if !EMISSIONS.exists(deps.storage) {
+        EMISSIONS.save(deps.storage, &Emissions::new(env.block.time))?;
+    }
+    if !BIOMASS_TWAP.exists(deps.storage) {
+        BIOMASS_TWAP.save(deps.storage, &BiomassTwap::new(env.block.time))?;
+    }
+    if !CANOPY_EPOCH.exists(deps.storage) {
+        let epoch = snapshot_sun_line(deps.storage, 0, env.block.time)?;
+        CANOPY_EPOCH.save(deps.storage, &epoch)?;
+        record_ecosystem_snapshot(deps.storage, &epoch)?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("version", CONTRACT_VERSION))
 }
 
 #[cw_serde]
@@ -143,16 +340,50 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    accrue_emissions(deps.storage, &env)?;
+    advance_canopy_epoch(deps.storage, &env)?;
+
     match msg {
         ExecuteMsg::Spin {
             token_id,
             trait_target,
-        } => execute_spin(deps, env, info, token_id, trait_target),
+            pay_from_rewards,
+        } => execute_spin(
+            deps,
+            env,
+            info.sender,
+            Funds::Native(info.funds),
+            token_id,
+            trait_target,
+            pay_from_rewards,
+        ),
         ExecuteMsg::ResolveSpin { token_id } => execute_resolve_spin(deps, env, info, token_id),
-        ExecuteMsg::Harvest { token_id } => execute_harvest(deps, env, info, token_id),
+        ExecuteMsg::Harvest {
+            token_id,
+            recipient,
+            msg,
+        } => execute_harvest(deps, env, info, token_id, recipient, msg),
         ExecuteMsg::RequestAscend { token_id } => execute_request_ascend(deps, env, info, token_id),
         ExecuteMsg::ResolveAscend { token_id } => execute_resolve_ascend(deps, env, info, token_id),
-        ExecuteMsg::RequestMint {} => execute_request_mint(deps, env, info),
+        ExecuteMsg::RequestMint {} => {
+            execute_request_mint(deps, env, info.sender, Funds::Native(info.funds))
+        }
+        ExecuteMsg::Receive(wrapper) => execute_receive(deps, env, info, wrapper),
+        ExecuteMsg::WithdrawTreasury { amount, recipient } => {
+            execute_withdraw_treasury(deps, info, amount, recipient)
+        }
+        ExecuteMsg::UpdateFeeSplit {
+            fee_split,
+            burn_addr,
+        } => execute_update_fee_split(deps, info, fee_split, burn_addr),
+        ExecuteMsg::FundEmissions {} => {
+            execute_fund_emissions(deps, env, info.sender, Funds::Native(info.funds))
+        }
+        ExecuteMsg::UpdateEmissionSchedule {
+            rate_per_second,
+            halving_interval,
+        } => execute_update_emission_schedule(deps, env, info, rate_per_second, halving_interval),
+        ExecuteMsg::UpdateGameRules { rules } => execute_update_game_rules(deps, info, rules),
         ExecuteMsg::ResolveMint { mint_id } => execute_resolve_mint(deps, env, info, mint_id),
         ExecuteMsg::Recycle { token_id } => execute_recycle(deps, env, info, token_id),
         ExecuteMsg::RequestSplice {
@@ -162,6 +393,17 @@ pub fn execute(
         ExecuteMsg::ResolveSplice { splice_id } => {
             execute_resolve_splice(deps, env, info, splice_id)
         }
+        ExecuteMsg::RequestGeneEdit { token_id, slot } => execute_request_gene_edit(
+            deps,
+            env,
+            info.sender,
+            Funds::Native(info.funds),
+            token_id,
+            slot,
+        ),
+        ExecuteMsg::ResolveGeneEdit { token_id } => {
+            execute_resolve_gene_edit(deps, env, info, token_id)
+        }
         ExecuteMsg::AcceptMinterOwnership { cw721_contract } => {
             let inner_msg =
                 Cw721OwnableMsg::UpdateMinterOwnership(cw_ownable::Action::AcceptOwnership);
@@ -205,31 +447,514 @@ fn require_unlocked(deps: &DepsMut, token_id: &str) -> Result<(), ContractError>
     Ok(())
 }
 
-fn execute_request_mint(
+/// Entry point for cw20 payments: the token contract calls this after a `Send`
+fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    match &config.payment {
+        Denom::Cw20(token) if token == info.sender => {}
+        _ => return Err(ContractError::InvalidPayment {}),
+    }
+
+    let player = deps.api.addr_validate(&wrapper.sender)?;
+    let funds = Funds::Cw20(wrapper.amount);
+
+    match from_json(&wrapper.msg)? {
+        ReceiveMsg::Spin {
+            token_id,
+            trait_target,
+        } => execute_spin(deps, env, player, funds, token_id, trait_target, false),
+        ReceiveMsg::RequestMint {} => execute_request_mint(deps, env, player, funds),
+        ReceiveMsg::FundEmissions {} => execute_fund_emissions(deps, env, player, funds),
+        ReceiveMsg::RequestGeneEdit { token_id, slot } => {
+            execute_request_gene_edit(deps, env, player, funds, token_id, slot)
+        }
+    }
+}
+
+/// What a player sent along with a paid request
+enum Funds {
+    /// `info.funds` of a direct call
+    Native(Vec<Coin>),
+    /// Amount received through the cw20 `Receive` hook
+    Cw20(Uint128),
+}
+
+/// What is returned to the player after taking the price
+#[derive(Default)]
+struct Refund {
+    native: Vec<Coin>,
+    cw20: Uint128,
+}
+
+/// Take `price` in the payment asset out of `funds` and return everything else:
+/// the excess payment plus any other denoms sent along.
+fn collect_payment(payment: &Denom, funds: Funds, price: Uint128) -> Result<Refund, ContractError> {
+    match (payment, funds) {
+        (Denom::Native(payment_denom), Funds::Native(coins)) => {
+            let paid = coins
+                .iter()
+                .filter(|coin| &coin.denom == payment_denom)
+                .try_fold(Uint128::zero(), |total, coin| {
+                    total.checked_add(coin.amount)
+                })?;
+
+            if !price.is_zero() {
+                if !coins.iter().any(|coin| &coin.denom == payment_denom) {
+                    return Err(ContractError::InvalidPayment {});
+                }
+                if paid < price {
+                    return Err(ContractError::InsufficientFunds {});
+                }
+            }
+
+            let mut native: Vec<Coin> = coins
+                .into_iter()
+                .filter(|coin| &coin.denom != payment_denom && !coin.amount.is_zero())
+                .collect();
+            let excess = paid - price;
+            if !excess.is_zero() {
+                native.push(Coin::new(excess, payment_denom));
+            }
+            Ok(Refund {
+                native,
+                cw20: Uint128::zero(),
+            })
+        }
+        (Denom::Cw20(_), Funds::Cw20(amount)) => {
+            if amount < price {
+                return Err(ContractError::InsufficientFunds {});
+            }
+            Ok(Refund {
+                native: vec![],
+                cw20: amount - price,
+            })
+        }
+        // Paid requests must go through the cw20 hook; free ones just return the coins
+        (Denom::Cw20(_), Funds::Native(coins)) => {
+            if !price.is_zero() {
+                return Err(ContractError::InvalidPayment {});
+            }
+            Ok(Refund {
+                native: coins
+                    .into_iter()
+                    .filter(|coin| !coin.amount.is_zero())
+                    .collect(),
+                cw20: Uint128::zero(),
+            })
+        }
+        (Denom::Native(_), Funds::Cw20(_)) => Err(ContractError::InvalidPayment {}),
+    }
+}
+
+/// Message sending `amount` of the payment asset to `recipient`
+fn send_payment(payment: &Denom, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    Ok(match payment {
+        Denom::Native(denom) => BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin::new(amount, denom)],
+        }
+        .into(),
+        Denom::Cw20(token) => WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    })
+}
+
+/// Like `send_payment`, but calls `contract` with `msg` and the payment attached
+fn send_payment_with_msg(
+    payment: &Denom,
+    contract: &Addr,
+    amount: Uint128,
+    msg: Binary,
+) -> StdResult<CosmosMsg> {
+    Ok(match payment {
+        Denom::Native(denom) => WasmMsg::Execute {
+            contract_addr: contract.to_string(),
+            msg,
+            funds: vec![Coin::new(amount, denom)],
+        }
+        .into(),
+        Denom::Cw20(token) => WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Send {
+                contract: contract.to_string(),
+                amount,
+                msg,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    })
+}
+
+/// Send `refund` back to `recipient` in the same transaction
+fn add_refund(
+    response: Response,
+    payment: &Denom,
+    recipient: &Addr,
+    refund: Refund,
+) -> StdResult<Response> {
+    let mut refunded: Vec<String> = refund.native.iter().map(|coin| coin.to_string()).collect();
+    let mut response = response;
+
+    if !refund.native.is_empty() {
+        response = response.add_message(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: refund.native,
+        });
+    }
+    if let Denom::Cw20(token) = payment {
+        if !refund.cw20.is_zero() {
+            refunded.push(format!("{}{}", refund.cw20, token));
+            response = response.add_message(send_payment(payment, recipient, refund.cw20)?);
+        }
+    }
+
+    if refunded.is_empty() {
+        return Ok(response);
+    }
+    Ok(response.add_attribute("refund", refunded.join(",")))
+}
+
+/// Split a resolved payment per the fee config. The treasury share stays in the
+/// contract until withdrawn, the burn share is sent to the burn address, and
+/// `Fees::rewards` is left for the caller to distribute.
+fn take_fees(
+    storage: &mut dyn Storage,
+    config: &GameConfig,
+    amount: Uint128,
+) -> StdResult<(Fees, Vec<CosmosMsg>)> {
+    let fees = config.fee_split.split(amount);
+    if fees.treasury.is_zero() && fees.burn.is_zero() {
+        return Ok((fees, vec![]));
+    }
+
+    let mut treasury = TREASURY.may_load(storage)?.unwrap_or_default();
+    treasury.balance = treasury.balance.checked_add(fees.treasury)?;
+    treasury.total_accrued = treasury.total_accrued.checked_add(fees.treasury)?;
+    treasury.total_burned = treasury.total_burned.checked_add(fees.burn)?;
+    TREASURY.save(storage, &treasury)?;
+
+    let mut messages = vec![];
+    if let (false, Some(burn_addr)) = (fees.burn.is_zero(), &config.burn_addr) {
+        messages.push(send_payment(&config.payment, burn_addr, fees.burn)?);
+    }
+    Ok((fees, messages))
+}
+
+/// Write the emissions released since the last call into the reward index.
+/// Runs before every execute so handlers always see an up-to-date index.
+fn accrue_emissions(storage: &mut dyn Storage, env: &Env) -> StdResult<()> {
+    // Deployments from before emissions existed have nothing to accrue
+    let Some(mut emissions) = EMISSIONS.may_load(storage)? else {
+        return Ok(());
+    };
+    if env.block.time <= emissions.last_accrued {
+        return Ok(());
+    }
+
+    let mut global_state = GLOBAL_STATE.load(storage)?;
+    let released = emissions.accrue(global_state.total_shares, env.block.time)?;
+    if !released.is_zero() {
+        distribute_rewards(&mut global_state, released)?;
+        GLOBAL_STATE.save(storage, &global_state)?;
+    }
+    EMISSIONS.save(storage, &emissions)
+}
+
+/// Sun Line for a new epoch: the average canopy score of all active mushrooms
+fn snapshot_sun_line(
+    storage: &dyn Storage,
+    epoch: u64,
+    started_at: Timestamp,
+) -> StdResult<CanopyEpoch> {
+    let rules = CONFIG.load(storage)?.rules;
+    let biomass = BIOMASS.load(storage)?;
+    let stats = GAME_STATS.load(storage)?;
+    let active_mushrooms = stats.total_minted.saturating_sub(stats.total_burned);
+
+    let sun_line = if active_mushrooms == 0 {
+        Decimal::zero()
+    } else {
+        total_canopy_score(&rules, &biomass) / Decimal::from_ratio(active_mushrooms, 1u128)
+    };
+    Ok(CanopyEpoch {
+        epoch,
+        started_at,
+        sun_line,
+        active_mushrooms,
+    })
+}
+
+/// Epoch in effect at `now`, snapshotting a new Sun Line if a boundary has passed
+/// since the stored one. `None` before the contract was migrated to epochs.
+fn current_canopy_epoch(storage: &dyn Storage, now: Timestamp) -> StdResult<Option<CanopyEpoch>> {
+    let Some(epoch) = CANOPY_EPOCH.may_load(storage)? else {
+        return Ok(None);
+    };
+    let epoch_length = CONFIG.load(storage)?.epoch_length;
+    let elapsed = now.seconds().saturating_sub(epoch.started_at.seconds()) / epoch_length;
+    if elapsed == 0 {
+        return Ok(Some(epoch));
+    }
+
+    let started_at = epoch.started_at.plus_seconds(elapsed * epoch_length);
+    snapshot_sun_line(storage, epoch.epoch + elapsed, started_at).map(Some)
+}
+
+/// Roll over to a new canopy epoch when the current one has ended
+fn advance_canopy_epoch(storage: &mut dyn Storage, env: &Env) -> StdResult<()> {
+    let Some(epoch) = current_canopy_epoch(storage, env.block.time)? else {
+        return Ok(());
+    };
+    if CANOPY_EPOCH.load(storage)?.epoch != epoch.epoch {
+        CANOPY_EPOCH.save(storage, &epoch)?;
+        let mut twap = BIOMASS_TWAP.load(storage)?;
+        twap.roll_epoch(&BIOMASS.load(storage)?, epoch.started_at);
+        BIOMASS_TWAP.save(storage, &twap)?;
+        record_ecosystem_snapshot(storage, &epoch)?;
+    }
+    Ok(())
+}
+
+/// Save a new biomass split, accumulating the one it replaces into the TWAP
+fn save_biomass(
+    storage: &mut dyn Storage,
+    now: Timestamp,
+    biomass: &GlobalBiomass,
+) -> StdResult<()> {
+    let mut twap = BIOMASS_TWAP.load(storage)?;
+    twap.record(&BIOMASS.load(storage)?, now);
+    BIOMASS_TWAP.save(storage, &twap)?;
+    BIOMASS.save(storage, biomass)
+}
+
+/// Biomass harvests are weighed against, averaged since the start of the previous
+/// epoch so a mint or recycle just before harvesting barely moves it
+fn time_weighted_biomass(storage: &dyn Storage, now: Timestamp) -> StdResult<GlobalBiomass> {
+    let biomass = BIOMASS.load(storage)?;
+    let mut twap = BIOMASS_TWAP.load(storage)?;
+    if let Some(epoch) = current_canopy_epoch(storage, now)? {
+        // Queries see the rollover the next execute would make
+        if CANOPY_EPOCH.load(storage)?.epoch != epoch.epoch {
+            twap.roll_epoch(&biomass, epoch.started_at);
+        }
+    }
+    twap.average(&biomass, now)
+}
+
+/// Append the ecosystem state at the start of `epoch` to the history and drop
+/// snapshots that fell out of the retention window
+fn record_ecosystem_snapshot(storage: &mut dyn Storage, epoch: &CanopyEpoch) -> StdResult<()> {
+    let config = CONFIG.load(storage)?;
+    let biomass = BIOMASS.load(storage)?;
+    let global_state = GLOBAL_STATE.load(storage)?;
+    let stats = GAME_STATS.load(storage)?;
+    let active_supply = stats.total_minted.saturating_sub(stats.total_burned);
+
+    let snapshot = EcosystemSnapshot {
+        epoch: epoch.epoch,
+        time: epoch.started_at,
+        cap_multiplier: trait_multiplier(&config.rules, &biomass, &TraitTarget::Cap),
+        stem_multiplier: trait_multiplier(&config.rules, &biomass, &TraitTarget::Stem),
+        spores_multiplier: trait_multiplier(&config.rules, &biomass, &TraitTarget::Spores),
+        biomass,
+        sun_line: epoch.sun_line,
+        total_shares: global_state.total_shares,
+        active_supply,
+        // A price the curve can't compute doesn't stop the game; chart it as 0
+        mint_price: config.mint_pricing.price(active_supply).unwrap_or_default(),
+        total_mint_volume: stats.total_mint_volume,
+        total_spin_volume: stats.total_spin_volume,
+    };
+    ECOSYSTEM_HISTORY.save(storage, epoch.epoch, &snapshot)?;
+
+    // Epochs can be skipped when nothing happens, so this is usually one or two keys
+    let cutoff = epoch.epoch.saturating_sub(config.history_retention - 1);
+    let expired: Vec<u64> = ECOSYSTEM_HISTORY
+        .keys(
+            storage,
+            None,
+            Some(Bound::exclusive(cutoff)),
+            Order::Ascending,
+        )
+        .collect::<StdResult<_>>()?;
+    for key in expired {
+        ECOSYSTEM_HISTORY.remove(storage, key);
+    }
+    Ok(())
+}
+
+fn execute_fund_emissions(
+    deps: DepsMut,
+    env: Env,
+    funder: Addr,
+    funds: Funds,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Everything sent in the payment asset goes to the reserve
+    let amount = match (&config.payment, &funds) {
+        (Denom::Native(denom), Funds::Native(coins)) => coins
+            .iter()
+            .filter(|coin| &coin.denom == denom)
+            .try_fold(Uint128::zero(), |total, coin| {
+                total.checked_add(coin.amount)
+            })?,
+        (Denom::Cw20(_), Funds::Cw20(amount)) => *amount,
+        _ => Uint128::zero(),
+    };
+    if amount.is_zero() {
+        return Err(ContractError::InvalidPayment {});
+    }
+    let refund = collect_payment(&config.payment, funds, amount)?;
+
+    let mut emissions = EMISSIONS
+        .may_load(deps.storage)?
+        .unwrap_or_else(|| Emissions::new(env.block.time));
+    emissions.reserve = emissions.reserve.checked_add(amount)?;
+    EMISSIONS.save(deps.storage, &emissions)?;
+
+    let response = Response::new()
+        .add_attribute("action", "fund_emissions")
+        .add_attribute("amount", amount)
+        .add_attribute("reserve", emissions.reserve);
+    Ok(add_refund(response, &config.payment, &funder, refund)?)
+}
+
+fn execute_update_emission_schedule(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    rate_per_second: Uint128,
+    halving_interval: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    if halving_interval == Some(0) {
+        return Err(ContractError::InvalidEmissionSchedule {});
+    }
+
+    // Already accrued up to now by `execute`, so the new rate starts from here
+    let mut emissions = EMISSIONS
+        .may_load(deps.storage)?
+        .unwrap_or_else(|| Emissions::new(env.block.time));
+    emissions.schedule = EmissionSchedule {
+        rate_per_second,
+        halving_interval,
+        start: env.block.time,
+    };
+    emissions.last_accrued = env.block.time;
+    EMISSIONS.save(deps.storage, &emissions)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_emission_schedule")
+        .add_attribute("rate_per_second", rate_per_second)
+        .add_attribute(
+            "halving_interval",
+            halving_interval.map_or("none".to_string(), |i| i.to_string()),
+        ))
+}
+
+fn execute_update_game_rules(
+    deps: DepsMut,
+    info: MessageInfo,
+    rules: GameRules,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    rules.validate()?;
+    config.rules = rules;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "update_game_rules"))
+}
+
+fn execute_withdraw_treasury(
+    deps: DepsMut,
+    info: MessageInfo,
+    amount: Option<Uint128>,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut treasury = TREASURY.may_load(deps.storage)?.unwrap_or_default();
+    let amount = amount.unwrap_or(treasury.balance);
+    if amount.is_zero() {
+        return Err(ContractError::NoRewards {});
+    }
+    if amount > treasury.balance {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => config.admin,
+    };
+
+    treasury.balance -= amount;
+    treasury.total_withdrawn = treasury.total_withdrawn.checked_add(amount)?;
+    TREASURY.save(deps.storage, &treasury)?;
+
+    Ok(Response::new()
+        .add_message(send_payment(&config.payment, &recipient, amount)?)
+        .add_attribute("action", "withdraw_treasury")
+        .add_attribute("amount", amount)
+        .add_attribute("recipient", recipient))
+}
+
+fn execute_update_fee_split(
+    deps: DepsMut,
+    info: MessageInfo,
+    fee_split: FeeSplit,
+    burn_addr: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    if let Some(addr) = burn_addr {
+        config.burn_addr = Some(deps.api.addr_validate(&addr)?);
+    }
+    fee_split.validate(config.burn_addr.is_some())?;
+    config.fee_split = fee_split;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "update_fee_split"))
+}
+
+fn execute_request_mint(
+    deps: DepsMut,
+    env: Env,
+    player: Addr,
+    funds: Funds,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let stats = GAME_STATS.load(deps.storage)?;
 
     // 1. Calculate Bonding Curve Price
     let current_supply = stats.total_minted.saturating_sub(stats.total_burned);
-    let increment_total = config.mint_cost_increment * Uint128::from(current_supply);
-    let current_price = config.mint_cost + increment_total;
+    let current_price = config.mint_pricing.price(current_supply)?;
 
     // 2. Validate Payment
-    if current_price > Uint128::zero() {
-        let payment = info
-            .funds
-            .iter()
-            .find(|coin| coin.denom == config.payment_denom)
-            .ok_or(ContractError::InvalidPayment {})?;
-
-        if payment.amount < current_price {
-            return Err(ContractError::InsufficientFunds {});
-        }
-    }
+    let refund = collect_payment(&config.payment, funds, current_price)?;
 
     // 3. Assign token ID
     let current_id_num = MINT_COUNTER.load(deps.storage)?;
@@ -238,29 +963,29 @@ fn execute_request_mint(
     let mint_id = current_id_num.to_string();
 
     // 4. Calculate target drand round
-    let now = env.block.time.seconds();
-    let current_round = (now - DRAND_GENESIS) / DRAND_PERIOD;
-    let target_round = current_round + 1;
+    let target_round = target_drand_round(deps.as_ref(), &env, &config)?;
 
     // 5. Save pending state
     let pending = PendingMint {
-        player: info.sender,
+        player: player.clone(),
         payment_amount: current_price,
         target_round,
         mint_id: mint_id.clone(),
+        requested_at: env.block.time,
     };
     PENDING_MINTS.save(deps.storage, &mint_id, &pending)?;
 
-    Ok(Response::new()
+    let response = Response::new()
         .add_attribute("action", "request_mint")
         .add_attribute("mint_id", mint_id)
         .add_attribute("price_paid", current_price)
-        .add_attribute("target_round", target_round.to_string()))
+        .add_attribute("target_round", target_round.to_string());
+    Ok(add_refund(response, &config.payment, &player, refund)?)
 }
 
 fn execute_resolve_mint(
     mut deps: DepsMut,
-    _env: Env,
+    env: Env,
     _info: MessageInfo,
     mint_id: String,
 ) -> Result<Response, ContractError> {
@@ -270,11 +995,11 @@ fn execute_resolve_mint(
         .map_err(|_| ContractError::NoPendingMint {})?;
 
     // 1. Fetch Randomness from Oracle
-    let oracle_res: BeaconResponse = deps.querier.query_wasm_smart(
-        config.oracle_addr.to_string(),
-        &OracleQueryMsg::Beacon {
-            round: Uint64::from(pending.target_round),
-        },
+    let oracle_res = fetch_beacon(
+        deps.as_ref(),
+        &config,
+        pending.target_round,
+        pending.requested_at,
     )?;
 
     // 2. Generate Deterministic Genetics from drand randomness
@@ -299,32 +1024,30 @@ fn execute_resolve_mint(
         base_cap: 0,
         base_stem: 0,
         base_spores: 0,
+        generation: lineage::record_birth(deps.storage, &mint_id, &[], pending.target_round)?,
     };
     new_traits.recalculate_base_stats();
 
-    // 3. Calculate Shares & Distribute Rewards
+    // 3. Calculate Shares & Distribute Rewards (after protocol fees)
     let mut global_state = GLOBAL_STATE.load(deps.storage)?;
-    let initial_shares = calculate_shares(&new_traits);
+    let initial_shares = calculate_shares(&config.rules, &new_traits);
+    let (fees, fee_msgs) = take_fees(deps.storage, &config, pending.payment_amount)?;
 
-    if !global_state.total_shares.is_zero() && pending.payment_amount > Uint128::zero() {
-        let reward_per_share = pending.payment_amount.checked_div(global_state.total_shares)?;
-        global_state.global_reward_index = global_state
-            .global_reward_index
-            .checked_add(reward_per_share)?;
-    }
+    distribute_rewards(&mut global_state, fees.rewards)?;
 
     // Update Biomass
     let mut biomass = BIOMASS.load(deps.storage)?;
-    add_stats_to_globals(&mut biomass, &mut global_state, &new_traits);
+    add_stats_to_globals(&config.rules, &mut biomass, &mut global_state, &new_traits);
 
-    BIOMASS.save(deps.storage, &biomass)?;
+    save_biomass(deps.storage, env.block.time, &biomass)?;
     GLOBAL_STATE.save(deps.storage, &global_state)?;
 
     // 4. Save Token Info
     let token_info = TokenInfo {
         current_shares: initial_shares,
-        reward_debt: initial_shares.checked_mul(global_state.global_reward_index)?,
+        reward_debt: reward_debt(initial_shares, global_state.global_reward_index)?,
         pending_rewards: Uint128::zero(),
+        ascension_attempts: 0,
     };
     TOKEN_INFO.save(deps.storage, &mint_id, &token_info)?;
 
@@ -360,13 +1083,48 @@ fn execute_resolve_mint(
 
     Ok(Response::new()
         .add_message(mint_msg)
+        .add_messages(fee_msgs)
         .add_attribute("action", "resolve_mint")
         .add_attribute("token_id", mint_id)
         .add_attribute("price_paid", pending.payment_amount)
+        .add_attribute("treasury_fee", fees.treasury)
+        .add_attribute("burn_fee", fees.burn)
         .add_attribute("owner", pending.player))
 }
 
-fn calculate_shares(traits: &TraitExtension) -> Uint128 {
+/// Add `amount` to the reward index. Whatever the index can't represent exactly,
+/// or everything while nothing is staked, is carried over to the next distribution.
+fn distribute_rewards(global_state: &mut GlobalState, amount: Uint128) -> StdResult<()> {
+    let total = global_state.undistributed_dust.checked_add(amount)?;
+    if global_state.total_shares.is_zero() {
+        global_state.undistributed_dust = total;
+        return Ok(());
+    }
+
+    let increase = Decimal256::from_ratio(total, global_state.total_shares);
+    // `increase` is rounded down, so this never exceeds `total`
+    let distributed = Uint256::from(global_state.total_shares)
+        .checked_mul_ceil(increase)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+    global_state.global_reward_index = global_state.global_reward_index.checked_add(increase)?;
+    global_state.undistributed_dust = total - Uint128::try_from(distributed)?;
+    Ok(())
+}
+
+/// `shares * index`, rounded down so tokens never claim more than was distributed
+fn reward_debt(shares: Uint128, index: Decimal256) -> StdResult<Uint128> {
+    let debt = Uint256::from(shares)
+        .checked_mul_floor(index)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+    Ok(Uint128::try_from(debt)?)
+}
+
+/// Rewards a token earned since its `reward_debt` was last set
+fn accrued_rewards(token_info: &TokenInfo, index: Decimal256) -> StdResult<Uint128> {
+    Ok(reward_debt(token_info.current_shares, index)?.saturating_sub(token_info.reward_debt))
+}
+
+fn calculate_shares(rules: &GameRules, traits: &TraitExtension) -> Uint128 {
     // 1. Calculate Raw Power
     let cap_score = (traits.cap as i128) + (traits.base_cap as i128);
     let stem_score = (traits.stem as i128) + (traits.base_stem as i128);
@@ -377,10 +1135,7 @@ fn calculate_shares(traits: &TraitExtension) -> Uint128 {
     let quadratic_shares = raw_power.pow(2) as u128;
 
     // 3. Substrate Multiplier
-    let multiplier = match traits.substrate {
-        0..=4 => 1 + (traits.substrate as u128), // 1x, 2x, 3x, 4x, 5x
-        _ => 8,                                  // Level 5 is 8x
-    };
+    let multiplier = rules.share_multiplier(traits.substrate);
 
     Uint128::from(quadratic_shares * multiplier)
 }
@@ -388,9 +1143,11 @@ fn calculate_shares(traits: &TraitExtension) -> Uint128 {
 fn execute_spin(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
+    player: Addr,
+    funds: Funds,
     token_id: String,
     trait_target: TraitTarget,
+    pay_from_rewards: bool,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
@@ -418,47 +1175,49 @@ fn execute_spin(
             include_expired: None,
         },
     )?;
-    if owner_res.owner != info.sender.to_string() {
+    if owner_res.owner != player.to_string() {
         return Err(ContractError::Unauthorized {});
     }
 
     let traits = parse_traits(nft_info.extension);
 
     // 2. Calculate Cost
-    let cost_multiplier: u128 = match traits.substrate {
-        0 => 1,
-        1 => 2,
-        2 => 3,
-        3 => 5,
-        4 => 10,
-        _ => 20, // Level 5 costs 20x to spin (High Risk/Reward)
-    };
+    let cost_multiplier = config.rules.spin_cost_multiplier(traits.substrate);
     let required_payment = config
         .spin_cost
         .checked_mul(Uint128::from(cost_multiplier))?;
 
     // 3. Take Payment
-    let payment = info
-        .funds
-        .iter()
-        .find(|c| c.denom == config.payment_denom)
-        .ok_or(ContractError::InvalidPayment {})?;
-    if payment.amount < required_payment {
-        return Err(ContractError::InsufficientFunds {});
-    }
+    let refund = if pay_from_rewards {
+        // The rewards are already in the contract, so resolution distributes them
+        // like any other bid
+        let global_state = GLOBAL_STATE.load(deps.storage)?;
+        let mut token_info = TOKEN_INFO.load(deps.storage, &token_id)?;
+        let accrued = accrued_rewards(&token_info, global_state.global_reward_index)?;
+        token_info.pending_rewards = token_info
+            .pending_rewards
+            .checked_add(accrued)?
+            .checked_sub(required_payment)
+            .map_err(|_| ContractError::InsufficientFunds {})?;
+        token_info.reward_debt =
+            reward_debt(token_info.current_shares, global_state.global_reward_index)?;
+        TOKEN_INFO.save(deps.storage, &token_id, &token_info)?;
+        collect_payment(&config.payment, funds, Uint128::zero())?
+    } else {
+        collect_payment(&config.payment, funds, required_payment)?
+    };
 
     // 4. Calculate Target Round (Current + 1)
-    let now = env.block.time.seconds();
-    let current_round = (now - DRAND_GENESIS) / DRAND_PERIOD;
-    let target_round = current_round + 1;
+    let target_round = target_drand_round(deps.as_ref(), &env, &config)?;
 
     // 5. Save Pending State
     let pending = PendingSpin {
         token_id: token_id.clone(),
-        player: info.sender,
+        player: player.clone(),
         target: trait_target,
         bid_amount: required_payment,
         target_round,
+        requested_at: env.block.time,
     };
     PENDING_SPINS.save(deps.storage, &token_id, &pending)?;
 
@@ -469,10 +1228,12 @@ fn execute_spin(
     // We do NOT distribute rewards yet. We hold the funds in the contract until resolution.
     // If we distributed now, and the spin failed/timeout, we couldn't refund easily.
 
-    Ok(Response::new()
+    let response = Response::new()
         .add_attribute("action", "request_spin")
         .add_attribute("token_id", token_id)
-        .add_attribute("target_round", target_round.to_string()))
+        .add_attribute("target_round", target_round.to_string())
+        .add_attribute("paid_from_rewards", pay_from_rewards.to_string());
+    Ok(add_refund(response, &config.payment, &player, refund)?)
 }
 
 fn execute_resolve_spin(
@@ -487,11 +1248,11 @@ fn execute_resolve_spin(
         .map_err(|_| ContractError::NoPendingSpin {})?;
 
     // 1. Fetch Randomness from Oracle
-    let oracle_res: BeaconResponse = deps.querier.query_wasm_smart(
-        config.oracle_addr.to_string(),
-        &OracleQueryMsg::Beacon {
-            round: Uint64::from(pending.target_round),
-        },
+    let oracle_res = fetch_beacon(
+        deps.as_ref(),
+        &config,
+        pending.target_round,
+        pending.requested_at,
     )?;
 
     // 2. Generate Deterministic Result
@@ -504,7 +1265,11 @@ fn execute_resolve_spin(
 
     // 3. Load Data needed for Game Logic
     let mut global_state = GLOBAL_STATE.load(deps.storage)?;
-    let mut token_info = TOKEN_INFO.may_load(deps.storage, &token_id)?.unwrap(); // Should exist
+    let mut token_info = TOKEN_INFO
+        .may_load(deps.storage, &token_id)?
+        .ok_or_else(|| ContractError::TokenNotFound {
+            token_id: token_id.clone(),
+        })?;
 
     let nft_info: cw721::msg::NftInfoResponse<NftExtensionMsg> = deps.querier.query_wasm_smart(
         config.cw721_addr.to_string(),
@@ -515,86 +1280,34 @@ fn execute_resolve_spin(
     let mut traits = parse_traits(nft_info.extension);
 
     // A. Update Pending Rewards (Accumulate)
-    if !token_info.current_shares.is_zero() && !global_state.total_shares.is_zero() {
-        let accrued = token_info
-            .current_shares
-            .checked_mul(global_state.global_reward_index)?
-            .checked_sub(token_info.reward_debt)?;
-        token_info.pending_rewards += accrued;
-    }
+    let accrued = accrued_rewards(&token_info, global_state.global_reward_index)?;
+    token_info.pending_rewards = token_info.pending_rewards.checked_add(accrued)?;
 
     // B. Determine Win/Loss
-    let success_threshold = if traits.substrate >= 3 { 140u8 } else { 128u8 };
-    let is_success = random_value >= success_threshold;
-
-    let (current_val, current_base) = match pending.target {
-        TraitTarget::Cap => (traits.cap, traits.base_cap),
-        TraitTarget::Stem => (traits.stem, traits.base_stem),
-        TraitTarget::Spores => (traits.spores, traits.base_spores),
-    };
-
-    let new_val = if is_success {
-        if traits.substrate >= 4 && random_value % 10 == 0 {
-            (current_val + 2).min(3)
-        } else if current_val == -1 {
-            1
-        } else {
-            (current_val + 1).min(3)
-        }
-    } else {
-        // Protection Logic
-        let primordial_count = count_primordial(&traits.genes);
-        let has_stability = primordial_count >= 3;
-
-        // Protection Logic
-        if has_stability {
-            // Do nothing on failure (Stable)
-            current_val
-        }
-        // Existing protections (Base 10 or Substrate 2)
-        else if current_base >= 10 || (current_val == 1 && traits.substrate >= 2) {
-            current_val
-        } else if current_val == 1 {
-            -1
-        } else {
-            (current_val - 1).max(-3)
-        }
-    };
+    let spin = spin_result(&config.rules, &traits, &pending.target, random_value);
+    let is_success = spin.success;
+    let current_val = spin.old_value;
+    let new_val = spin.new_value;
 
     // Apply
-    match pending.target {
-        TraitTarget::Cap => traits.cap = new_val,
-        TraitTarget::Stem => traits.stem = new_val,
-        TraitTarget::Spores => traits.spores = new_val,
-    }
+    set_trait_value(&mut traits, &pending.target, new_val);
 
     // C. Update Shares & Globals
-    let new_shares = calculate_shares(&traits);
+    let new_shares = calculate_shares(&config.rules, &traits);
     global_state.total_shares = global_state
         .total_shares
         .checked_sub(token_info.current_shares)?
         .checked_add(new_shares)?;
 
-    // D. Distribute the Payment NOW
+    // D. Distribute the Payment NOW (after protocol fees)
+    let (fees, fee_msgs) = take_fees(deps.storage, &config, pending.bid_amount)?;
     let old_index = global_state.global_reward_index;
-
-    if !global_state.total_shares.is_zero() {
-        let reward_per_share = pending.bid_amount.checked_div(global_state.total_shares)?;
-        global_state.global_reward_index += reward_per_share;
-    }
-
-    // Calculate how much the index went up due to this spin
-    let index_increase = global_state.global_reward_index.checked_sub(old_index)?;
-
-    // If the user has shares (post-spin), give them their slice of their own bid
-    if !new_shares.is_zero() {
-        let self_reward = new_shares.checked_mul(index_increase)?;
-        token_info.pending_rewards = token_info.pending_rewards.checked_add(self_reward)?;
-    }
+    distribute_rewards(&mut global_state, fees.rewards)?;
 
     // E. Update User's Debt
+    // Set at the pre-spin index so the token also earns its slice of its own bid
     token_info.current_shares = new_shares;
-    token_info.reward_debt = new_shares.checked_mul(global_state.global_reward_index)?;
+    token_info.reward_debt = reward_debt(new_shares, old_index)?;
 
     // 5. Save Everything
     GLOBAL_STATE.save(deps.storage, &global_state)?;
@@ -628,10 +1341,13 @@ fn execute_resolve_spin(
 
     Ok(Response::new()
         .add_message(update_msg)
+        .add_messages(fee_msgs)
         .add_attribute("action", "resolve_spin")
         .add_attribute("token_id", token_id)
         .add_attribute("random_value", random_value.to_string())
         .add_attribute("success", is_success.to_string())
+        .add_attribute("treasury_fee", fees.treasury)
+        .add_attribute("burn_fee", fees.burn)
         .add_attribute("trait_target", target_str)
         .add_attribute("old_value", current_val.to_string())
         .add_attribute("new_value", new_val.to_string()))
@@ -639,11 +1355,17 @@ fn execute_resolve_spin(
 
 fn execute_harvest(
     mut deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     token_id: String,
+    recipient: Option<String>,
+    msg: Option<Binary>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => info.sender.clone(),
+    };
 
     // 0. Check token is not locked
     require_unlocked(&deps, &token_id)?;
@@ -673,26 +1395,22 @@ fn execute_harvest(
     // 2. Load State
     let mut token_info = TOKEN_INFO.load(deps.storage, &token_id)?;
     let mut global_state = GLOBAL_STATE.load(deps.storage)?;
-    let biomass = BIOMASS.load(deps.storage)?;
+    let biomass = time_weighted_biomass(deps.storage, env.block.time)?;
+    let sun_line = CANOPY_EPOCH
+        .may_load(deps.storage)?
+        .map_or(Decimal::zero(), |epoch| epoch.sun_line);
 
     // 3. Accumulate Pending Rewards (Standard Staking Math)
-    if !token_info.current_shares.is_zero() {
-        let accumulated = token_info
-            .current_shares
-            .checked_mul(global_state.global_reward_index)?
-            .checked_sub(token_info.reward_debt)
-            .unwrap_or(Uint128::zero());
-
-        token_info.pending_rewards = token_info.pending_rewards.checked_add(accumulated)?;
-    }
+    let accumulated = accrued_rewards(&token_info, global_state.global_reward_index)?;
+    token_info.pending_rewards = token_info.pending_rewards.checked_add(accumulated)?;
 
     if token_info.pending_rewards.is_zero() {
         return Err(ContractError::NoRewards {});
     }
 
     // 4. Apply Canopy Multiplier (The Weather)
-    // We calculate how valuable this mushroom is in the CURRENT economy
-    let multiplier = calculate_canopy_multiplier(&biomass, &traits);
+    // We calculate how valuable this mushroom is in the recent economy
+    let multiplier = calculate_canopy_multiplier(&config.rules, &biomass, &traits, sun_line);
 
     // Apply multiplier to the payout
     // Payout = Pending * Multiplier
@@ -706,12 +1424,7 @@ fn execute_harvest(
     };
 
     // If money was lost (Penalty), recycle it back to the global pool
-    if !forfeited_amount.is_zero() && !global_state.total_shares.is_zero() {
-        let recycle_per_share = forfeited_amount.checked_div(global_state.total_shares)?;
-        global_state.global_reward_index = global_state
-            .global_reward_index
-            .checked_add(recycle_per_share)?;
-    }
+    distribute_rewards(&mut global_state, forfeited_amount)?;
 
     // Shadow Zone check (if multiplier was 0)
     if payout_amount.is_zero() {
@@ -726,16 +1439,10 @@ fn execute_harvest(
         stats.total_rewards_distributed += payout_amount;
         GAME_STATS.save(deps.storage, &stats)?;
 
-        messages.push(
-            BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![Coin {
-                    denom: config.payment_denom.clone(),
-                    amount: payout_amount,
-                }],
-            }
-            .into(),
-        );
+        messages.push(match msg {
+            Some(msg) => send_payment_with_msg(&config.payment, &recipient, payout_amount, msg)?,
+            None => send_payment(&config.payment, &recipient, payout_amount)?,
+        });
     }
 
     let mut stats = GAME_STATS.load(deps.storage)?;
@@ -775,7 +1482,7 @@ fn execute_harvest(
     // 7. Recalculate Shares & Update Globals
     // Note: Biomass doesn't change during harvest (Base stats are permanent), only Shares change (Volatile stats reset)
     let old_shares = token_info.current_shares;
-    let new_shares = calculate_shares(&traits);
+    let new_shares = calculate_shares(&config.rules, &traits);
 
     global_state.total_shares = global_state
         .total_shares
@@ -784,7 +1491,7 @@ fn execute_harvest(
 
     token_info.current_shares = new_shares;
     token_info.pending_rewards = Uint128::zero();
-    token_info.reward_debt = new_shares.checked_mul(global_state.global_reward_index)?;
+    token_info.reward_debt = reward_debt(new_shares, global_state.global_reward_index)?;
 
     // 8. Save
     GLOBAL_STATE.save(deps.storage, &global_state)?;
@@ -809,6 +1516,7 @@ fn execute_harvest(
         .add_messages(messages)
         .add_attribute("action", "harvest")
         .add_attribute("token_id", token_id)
+        .add_attribute("recipient", recipient)
         .add_attribute("base_payout", token_info.pending_rewards) // What they had pending
         .add_attribute("canopy_multiplier", multiplier.to_string())
         .add_attribute("final_payout", payout_amount))
@@ -850,7 +1558,7 @@ fn execute_request_ascend(
         return Err(ContractError::NotMaxLevel {});
     }
 
-    if traits.substrate >= 4 {
+    if traits.substrate >= MAX_ASCEND_SUBSTRATE {
         return Err(ContractError::MaxSubstrate {});
     }
 
@@ -859,14 +1567,10 @@ fn execute_request_ascend(
 
     // Accumulate pending rewards before capturing
     if !token_info.current_shares.is_zero() && !global_state.total_shares.is_zero() {
-        let accrued = token_info
-            .current_shares
-            .checked_mul(global_state.global_reward_index)?
-            .checked_sub(token_info.reward_debt)?;
+        let accrued = accrued_rewards(&token_info, global_state.global_reward_index)?;
         token_info.pending_rewards += accrued;
-        token_info.reward_debt = token_info
-            .current_shares
-            .checked_mul(global_state.global_reward_index)?;
+        token_info.reward_debt =
+            reward_debt(token_info.current_shares, global_state.global_reward_index)?;
         TOKEN_INFO.save(deps.storage, &token_id, &token_info)?;
     }
 
@@ -877,9 +1581,7 @@ fn execute_request_ascend(
     let burned_amount = token_info.pending_rewards;
 
     // Calculate target round
-    let now = env.block.time.seconds();
-    let current_round = (now - DRAND_GENESIS) / DRAND_PERIOD;
-    let target_round = current_round + 1;
+    let target_round = target_drand_round(deps.as_ref(), &env, &config)?;
 
     // Lock the token
     LOCKED_TOKENS.save(deps.storage, &token_id, &"ascend".to_string())?;
@@ -890,6 +1592,7 @@ fn execute_request_ascend(
         token_id: token_id.clone(),
         target_round,
         burned_amount,
+        requested_at: env.block.time,
     };
     PENDING_ASCENDS.save(deps.storage, &token_id, &pending)?;
 
@@ -911,11 +1614,11 @@ fn execute_resolve_ascend(
         .map_err(|_| ContractError::NoPendingAscend {})?;
 
     // 1. Fetch Randomness from Oracle
-    let oracle_res: BeaconResponse = deps.querier.query_wasm_smart(
-        config.oracle_addr.to_string(),
-        &OracleQueryMsg::Beacon {
-            round: Uint64::from(pending.target_round),
-        },
+    let oracle_res = fetch_beacon(
+        deps.as_ref(),
+        &config,
+        pending.target_round,
+        pending.requested_at,
     )?;
 
     // 2. Deterministic result from drand randomness
@@ -923,9 +1626,23 @@ fn execute_resolve_ascend(
     hasher.update(oracle_res.uniform_seed);
     hasher.update(token_id.as_bytes());
     let result_hash = hasher.finalize();
-    let random = result_hash[0];
+    let random = u32::from_be_bytes([
+        result_hash[0],
+        result_hash[1],
+        result_hash[2],
+        result_hash[3],
+    ]);
 
-    let success = random % 5 == 0; // 20% chance
+    let mut token_info = TOKEN_INFO.load(deps.storage, &token_id)?;
+    let success_chance = config.rules.ascend_chance(token_info.ascension_attempts);
+    let success = config
+        .rules
+        .ascend_succeeds(random, token_info.ascension_attempts);
+    token_info.ascension_attempts = if success {
+        0
+    } else {
+        token_info.ascension_attempts + 1
+    };
 
     // 3. Load and update traits
     let nft_info: cw721::msg::NftInfoResponse<NftExtensionMsg> = deps.querier.query_wasm_smart(
@@ -946,40 +1663,43 @@ fn execute_resolve_ascend(
         traits.substrate += 1;
     }
 
-    traits.cap = 0;
-    traits.stem = 0;
-    traits.spores = 0;
+    if success || config.rules.ascend_failure_resets_stats {
+        traits.cap = 0;
+        traits.stem = 0;
+        traits.spores = 0;
+    }
 
     // 4. Update game state
-    let mut token_info = TOKEN_INFO.load(deps.storage, &token_id)?;
     let mut global_state = GLOBAL_STATE.load(deps.storage)?;
 
-    token_info.pending_rewards = Uint128::zero();
+    // Rewards distributed while the ascend was pending were earned on the old shares
+    let accrued = accrued_rewards(&token_info, global_state.global_reward_index)?;
+
+    // A failure may keep part of the captured rewards pending
+    let recycled = if success {
+        pending.burned_amount
+    } else {
+        config.rules.ascend_failure_burn(pending.burned_amount)
+    };
+    token_info.pending_rewards = (pending.burned_amount - recycled).checked_add(accrued)?;
 
     global_state.total_shares = global_state
         .total_shares
         .checked_sub(token_info.current_shares)?;
-    let new_shares = calculate_shares(&traits);
+    let new_shares = calculate_shares(&config.rules, &traits);
     global_state.total_shares = global_state.total_shares.checked_add(new_shares)?;
 
-    if !global_state.total_shares.is_zero() {
-        let reward_part = pending.burned_amount.u128();
-        let reward_per_share = reward_part / global_state.total_shares.u128();
-
-        global_state.global_reward_index = global_state
-            .global_reward_index
-            .checked_add(Uint128::from(reward_per_share))?;
-    }
+    distribute_rewards(&mut global_state, recycled)?;
 
     token_info.current_shares = new_shares;
-    token_info.reward_debt = new_shares.checked_mul(global_state.global_reward_index)?;
+    token_info.reward_debt = reward_debt(new_shares, global_state.global_reward_index)?;
 
     GLOBAL_STATE.save(deps.storage, &global_state)?;
     TOKEN_INFO.save(deps.storage, &token_id, &token_info)?;
 
     let mut stats = GAME_STATS.load(deps.storage)?;
     stats.total_ascensions += 1;
-    stats.total_rewards_recycled += pending.burned_amount;
+    stats.total_rewards_recycled += recycled;
     GAME_STATS.save(deps.storage, &stats)?;
 
     update_leaderboard(&mut deps, token_id.clone(), new_shares)?;
@@ -1003,7 +1723,13 @@ fn execute_resolve_ascend(
         .add_attribute("action", "resolve_ascend")
         .add_attribute("token_id", token_id)
         .add_attribute("success", success.to_string())
-        .add_attribute("new_substrate", new_substrate.to_string()))
+        .add_attribute("success_chance_bps", success_chance.to_string())
+        .add_attribute(
+            "ascension_attempts",
+            token_info.ascension_attempts.to_string(),
+        )
+        .add_attribute("new_substrate", new_substrate.to_string())
+        .add_attribute("recycled_amount", recycled))
 }
 
 fn execute_request_splice(
@@ -1034,9 +1760,7 @@ fn execute_request_splice(
     let splice_id = current_id.to_string();
 
     // 3. Calculate target round
-    let now = env.block.time.seconds();
-    let current_round = (now - DRAND_GENESIS) / DRAND_PERIOD;
-    let target_round = current_round + 1;
+    let target_round = target_drand_round(deps.as_ref(), &env, &config)?;
 
     // 4. Lock both parents
     LOCKED_TOKENS.save(deps.storage, &parent_1_id, &"splice".to_string())?;
@@ -1049,6 +1773,7 @@ fn execute_request_splice(
         parent_2_id: parent_2_id.clone(),
         target_round,
         splice_id: splice_id.clone(),
+        requested_at: env.block.time,
     };
     PENDING_SPLICES.save(deps.storage, &splice_id, &pending)?;
 
@@ -1062,7 +1787,7 @@ fn execute_request_splice(
 
 fn execute_resolve_splice(
     mut deps: DepsMut,
-    _env: Env,
+    env: Env,
     _info: MessageInfo,
     splice_id: String,
 ) -> Result<Response, ContractError> {
@@ -1072,11 +1797,11 @@ fn execute_resolve_splice(
         .map_err(|_| ContractError::NoPendingSplice {})?;
 
     // 1. Fetch Randomness from Oracle
-    let oracle_res: BeaconResponse = deps.querier.query_wasm_smart(
-        config.oracle_addr.to_string(),
-        &OracleQueryMsg::Beacon {
-            round: Uint64::from(pending.target_round),
-        },
+    let oracle_res = fetch_beacon(
+        deps.as_ref(),
+        &config,
+        pending.target_round,
+        pending.requested_at,
     )?;
 
     // 2. Load parent traits
@@ -1105,25 +1830,35 @@ fn execute_resolve_splice(
 
     let calculate_forfeit = |token_id: &str, g_state: &GlobalState| -> StdResult<Uint128> {
         let t_info = TOKEN_INFO.load(deps.storage, token_id)?;
-        let mut pending_rw = t_info.pending_rewards;
-        if !t_info.current_shares.is_zero() {
-            let accrued = t_info
-                .current_shares
-                .checked_mul(g_state.global_reward_index)?
-                .checked_sub(t_info.reward_debt)
-                .unwrap_or(Uint128::zero());
-            pending_rw += accrued;
-        }
-        Ok(pending_rw)
+        let accrued = accrued_rewards(&t_info, g_state.global_reward_index)?;
+        Ok(t_info.pending_rewards.checked_add(accrued)?)
     };
 
     total_forfeited += calculate_forfeit(&pending.parent_1_id, &global_state)?;
     total_forfeited += calculate_forfeit(&pending.parent_2_id, &global_state)?;
 
     // 4. Remove parents from ecosystem
-    remove_stats_from_globals(&mut biomass, &mut global_state, &parent_1_traits);
-    remove_stats_from_globals(&mut biomass, &mut global_state, &parent_2_traits);
+    let parent_1_shares = TOKEN_INFO
+        .load(deps.storage, &pending.parent_1_id)?
+        .current_shares;
+    let parent_2_shares = TOKEN_INFO
+        .load(deps.storage, &pending.parent_2_id)?
+        .current_shares;
+    remove_stats_from_globals(
+        &mut biomass,
+        &mut global_state,
+        &parent_1_traits,
+        parent_1_shares,
+    );
+    remove_stats_from_globals(
+        &mut biomass,
+        &mut global_state,
+        &parent_2_traits,
+        parent_2_shares,
+    );
 
+    lineage::record_burn(deps.storage, &pending.parent_1_id)?;
+    lineage::record_burn(deps.storage, &pending.parent_2_id)?;
     TOKEN_INFO.remove(deps.storage, &pending.parent_1_id);
     TOKEN_INFO.remove(deps.storage, &pending.parent_2_id);
 
@@ -1131,12 +1866,7 @@ fn execute_resolve_splice(
     remove_from_leaderboard(&mut deps, &pending.parent_2_id)?;
 
     // Recycle forfeited rewards to survivors
-    if !total_forfeited.is_zero() && !global_state.total_shares.is_zero() {
-        let recycle_per_share = total_forfeited.checked_div(global_state.total_shares)?;
-        global_state.global_reward_index = global_state
-            .global_reward_index
-            .checked_add(recycle_per_share)?;
-    }
+    distribute_rewards(&mut global_state, total_forfeited)?;
 
     // 5. Generate child genes from drand randomness
     let mut hasher = Sha256::new();
@@ -1145,42 +1875,41 @@ fn execute_resolve_splice(
     hasher.update(pending.parent_2_id.as_bytes());
     let hash = hasher.finalize();
 
-    let mut child_genes: Vec<u8> = Vec::with_capacity(8);
-    for i in 0..8 {
-        let rng_byte = hash[i];
-        if rng_byte < 13 {
-            // 5% Mutation
-            if rng_byte < 2 {
-                child_genes.push(4); // Primordial
-            } else {
-                child_genes.push(0); // Rot
-            }
-        } else {
+    // Genetic Decay: higher substrate parents mutate to Rot more often
+    let mutation = config
+        .rules
+        .splice_mutation(parent_1_traits.substrate, parent_2_traits.substrate);
+    let mut primordial_mutations = 0u32;
+    let mut rot_mutations = 0u32;
+
+    let mut child_genes: Vec<u8> = Vec::with_capacity(GENE_SLOTS);
+    for i in 0..GENE_SLOTS {
+        let outcome = slot_outcome(mutation, hash[i]);
+        match outcome {
+            SlotOutcome::Primordial => primordial_mutations += 1,
+            SlotOutcome::Rot => rot_mutations += 1,
             // Inheritance
-            if rng_byte % 2 == 0 {
-                child_genes.push(parent_1_traits.genes.get(i).cloned().unwrap_or(0));
-            } else {
-                child_genes.push(parent_2_traits.genes.get(i).cloned().unwrap_or(0));
-            }
+            SlotOutcome::Parent1 | SlotOutcome::Parent2 => {}
         }
+        child_genes.push(outcome.gene(
+            parent_1_traits.genes.get(i).cloned().unwrap_or(0),
+            parent_2_traits.genes.get(i).cloned().unwrap_or(0),
+        ));
     }
 
-    let sum_substrate = parent_1_traits.substrate + parent_2_traits.substrate;
-    let mut inherited_substrate = sum_substrate / 2;
-
-    // Synergy Bonus (Chance to upgrade if parents are equal)
-    let mutation_byte = hash[8];
-    if parent_1_traits.substrate > 0 && parent_1_traits.substrate == parent_2_traits.substrate
-        && mutation_byte < 51
-    {
-        inherited_substrate += 1;
-    }
-
-    if inherited_substrate > 5 {
-        inherited_substrate = 5;
-    }
+    let inherited_substrate = inherit_substrate(
+        parent_1_traits.substrate,
+        parent_2_traits.substrate,
+        hash[8],
+    );
 
     // 6. Create Child
+    let generation = lineage::record_birth(
+        deps.storage,
+        &splice_id,
+        &[&pending.parent_1_id, &pending.parent_2_id],
+        pending.target_round,
+    )?;
     let mut child_traits = TraitExtension {
         cap: 0,
         stem: 0,
@@ -1190,12 +1919,18 @@ fn execute_resolve_splice(
         base_cap: 0,
         base_stem: 0,
         base_spores: 0,
+        generation,
     };
     child_traits.recalculate_base_stats();
 
-    add_stats_to_globals(&mut biomass, &mut global_state, &child_traits);
+    add_stats_to_globals(
+        &config.rules,
+        &mut biomass,
+        &mut global_state,
+        &child_traits,
+    );
 
-    BIOMASS.save(deps.storage, &biomass)?;
+    save_biomass(deps.storage, env.block.time, &biomass)?;
     GLOBAL_STATE.save(deps.storage, &global_state)?;
 
     // Update Stats
@@ -1206,11 +1941,12 @@ fn execute_resolve_splice(
     game_stats.total_rewards_recycled += total_forfeited;
     GAME_STATS.save(deps.storage, &game_stats)?;
 
-    let child_shares = calculate_shares(&child_traits);
+    let child_shares = calculate_shares(&config.rules, &child_traits);
     let child_info = TokenInfo {
         current_shares: child_shares,
-        reward_debt: child_shares.checked_mul(global_state.global_reward_index)?,
+        reward_debt: reward_debt(child_shares, global_state.global_reward_index)?,
         pending_rewards: Uint128::zero(),
+        ascension_attempts: 0,
     };
     TOKEN_INFO.save(deps.storage, &splice_id, &child_info)?;
 
@@ -1265,12 +2001,180 @@ fn execute_resolve_splice(
         .add_attribute("parent_1", pending.parent_1_id)
         .add_attribute("parent_2", pending.parent_2_id)
         .add_attribute("child_id", splice_id)
-        .add_attribute("recycled_amount", total_forfeited))
+        .add_attribute("generation", generation.to_string())
+        .add_attribute("recycled_amount", total_forfeited)
+        .add_attribute(
+            "mutations",
+            (rot_mutations + primordial_mutations).to_string(),
+        )
+        .add_attribute("rot_mutations", rot_mutations.to_string())
+        .add_attribute("primordial_mutations", primordial_mutations.to_string()))
+}
+
+fn execute_request_gene_edit(
+    deps: DepsMut,
+    env: Env,
+    player: Addr,
+    funds: Funds,
+    token_id: String,
+    slot: u8,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    require_unlocked(&deps, &token_id)?;
+
+    // A pending spin would resolve against the genes it was priced on
+    if PENDING_SPINS.has(deps.storage, &token_id) {
+        return Err(ContractError::HasPendingSpin {});
+    }
+
+    let owner_response: cw721::msg::OwnerOfResponse = deps.querier.query_wasm_smart(
+        config.cw721_addr.to_string(),
+        &cw721::msg::Cw721QueryMsg::<NftExtensionMsg, Empty, Empty>::OwnerOf {
+            token_id: token_id.clone(),
+            include_expired: None,
+        },
+    )?;
+    if owner_response.owner != player.to_string() {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let traits = query_traits(deps.as_ref(), &config, &token_id)?;
+    let gene = match traits.genes.get(slot as usize) {
+        Some(&gene) if (slot as usize) < GENE_SLOTS => gene,
+        _ => return Err(ContractError::InvalidGeneSlot { slot }),
+    };
+
+    // The spin cost for the substrate, scaled by the rarity of the gene replaced
+    let cost = config
+        .spin_cost
+        .checked_mul(Uint128::from(
+            config.rules.spin_cost_multiplier(traits.substrate),
+        ))?
+        .checked_mul(Uint128::from(config.rules.gene_edit_multiplier(gene)))?;
+    let refund = collect_payment(&config.payment, funds, cost)?;
+
+    let target_round = target_drand_round(deps.as_ref(), &env, &config)?;
+
+    LOCKED_TOKENS.save(deps.storage, &token_id, &"gene_edit".to_string())?;
+
+    let pending = PendingGeneEdit {
+        player: player.clone(),
+        token_id: token_id.clone(),
+        slot,
+        bid_amount: cost,
+        target_round,
+        requested_at: env.block.time,
+    };
+    PENDING_GENE_EDITS.save(deps.storage, &token_id, &pending)?;
+
+    let response = Response::new()
+        .add_attribute("action", "request_gene_edit")
+        .add_attribute("token_id", token_id)
+        .add_attribute("slot", slot.to_string())
+        .add_attribute("cost", cost)
+        .add_attribute("target_round", target_round.to_string());
+    Ok(add_refund(response, &config.payment, &player, refund)?)
+}
+
+fn execute_resolve_gene_edit(
+    mut deps: DepsMut,
+    env: Env,
+    _info: MessageInfo, // Anyone can call this (Public Keeper)
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let pending = PENDING_GENE_EDITS
+        .load(deps.storage, &token_id)
+        .map_err(|_| ContractError::NoPendingGeneEdit {})?;
+
+    // 1. Fetch Randomness from Oracle
+    let oracle_res = fetch_beacon(
+        deps.as_ref(),
+        &config,
+        pending.target_round,
+        pending.requested_at,
+    )?;
+
+    // 2. Domain-separated from a spin or ascend on the same round
+    let mut hasher = Sha256::new();
+    hasher.update(oracle_res.uniform_seed);
+    hasher.update(token_id.as_bytes());
+    hasher.update(b"gene_edit");
+    let hash = hasher.finalize();
+
+    let mut global_state = GLOBAL_STATE.load(deps.storage)?;
+    let mut biomass = BIOMASS.load(deps.storage)?;
+    let mut token_info = TOKEN_INFO.load(deps.storage, &token_id)?;
+    let mut traits = query_traits(deps.as_ref(), &config, &token_id)?;
+
+    let accrued = accrued_rewards(&token_info, global_state.global_reward_index)?;
+    token_info.pending_rewards = token_info.pending_rewards.checked_add(accrued)?;
+
+    // 3. Re-roll the slot with the mutation odds of a splice of two of this level
+    remove_stats_from_globals(
+        &mut biomass,
+        &mut global_state,
+        &traits,
+        token_info.current_shares,
+    );
+    let slot = pending.slot as usize;
+    let old_gene = traits.genes[slot];
+    let mutation = config
+        .rules
+        .splice_mutation(traits.substrate, traits.substrate);
+    let new_gene = reroll_gene(mutation, hash[0], hash[1]);
+    traits.genes[slot] = new_gene;
+    traits.recalculate_base_stats();
+    add_stats_to_globals(&config.rules, &mut biomass, &mut global_state, &traits);
+    let new_shares = calculate_shares(&config.rules, &traits);
+
+    // 4. Distribute the payment like a spin's
+    let (fees, fee_msgs) = take_fees(deps.storage, &config, pending.bid_amount)?;
+    let old_index = global_state.global_reward_index;
+    distribute_rewards(&mut global_state, fees.rewards)?;
+
+    token_info.current_shares = new_shares;
+    token_info.reward_debt = reward_debt(new_shares, old_index)?;
+
+    save_biomass(deps.storage, env.block.time, &biomass)?;
+    GLOBAL_STATE.save(deps.storage, &global_state)?;
+    TOKEN_INFO.save(deps.storage, &token_id, &token_info)?;
+
+    update_leaderboard(&mut deps, token_id.clone(), new_shares)?;
+
+    let mut stats = GAME_STATS.load(deps.storage)?;
+    stats.total_gene_edits += 1;
+    GAME_STATS.save(deps.storage, &stats)?;
+
+    // 5. Cleanup
+    PENDING_GENE_EDITS.remove(deps.storage, &token_id);
+    LOCKED_TOKENS.remove(deps.storage, &token_id);
+
+    let update_msg = WasmMsg::Execute {
+        contract_addr: config.cw721_addr.to_string(),
+        msg: to_json_binary(&spore_fates::cw721::ExecuteMsg::UpdateTraits {
+            token_id: token_id.clone(),
+            traits,
+        })?,
+        funds: vec![],
+    };
+
+    Ok(Response::new()
+        .add_message(update_msg)
+        .add_messages(fee_msgs)
+        .add_attribute("action", "resolve_gene_edit")
+        .add_attribute("token_id", token_id)
+        .add_attribute("slot", pending.slot.to_string())
+        .add_attribute("old_gene", old_gene.to_string())
+        .add_attribute("new_gene", new_gene.to_string())
+        .add_attribute("treasury_fee", fees.treasury)
+        .add_attribute("burn_fee", fees.burn))
 }
 
 fn execute_recycle(
     mut deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     token_id: String,
 ) -> Result<Response, ContractError> {
@@ -1279,17 +2183,48 @@ fn execute_recycle(
     // 0. Check token is not locked
     require_unlocked(&deps, &token_id)?;
 
+    // A pending spin would resolve against a token that no longer exists
+    if PENDING_SPINS.has(deps.storage, &token_id) {
+        return Err(ContractError::HasPendingSpin {});
+    }
+
     // 1. Verify Ownership & Load Traits
     let traits = load_and_verify_nft(&deps, &config.cw721_addr, &token_id, &info.sender)?;
 
-    // 2. Remove from Game State
+    // 2. Settle Rewards under the same canopy rules as a harvest
     let mut global_state = GLOBAL_STATE.load(deps.storage)?;
+    let token_info = TOKEN_INFO.load(deps.storage, &token_id)?;
+    let accrued = accrued_rewards(&token_info, global_state.global_reward_index)?;
+    let pending = token_info.pending_rewards.checked_add(accrued)?;
+
+    let sun_line = CANOPY_EPOCH
+        .may_load(deps.storage)?
+        .map_or(Decimal::zero(), |epoch| epoch.sun_line);
+    let multiplier = calculate_canopy_multiplier(
+        &config.rules,
+        &time_weighted_biomass(deps.storage, env.block.time)?,
+        &traits,
+        sun_line,
+    );
+    let payout_amount = pending.mul_floor(multiplier);
+    let forfeited_amount = pending.saturating_sub(payout_amount);
+
+    // 3. Remove from Game State
     let mut biomass = BIOMASS.load(deps.storage)?;
 
     // (Helper we defined earlier)
-    remove_stats_from_globals(&mut biomass, &mut global_state, &traits);
+    remove_stats_from_globals(
+        &mut biomass,
+        &mut global_state,
+        &traits,
+        token_info.current_shares,
+    );
+
+    // Forfeits go to the mushrooms still growing, like a splice
+    distribute_rewards(&mut global_state, forfeited_amount)?;
 
     // Remove from internal maps
+    lineage::record_burn(deps.storage, &token_id)?;
     TOKEN_INFO.remove(deps.storage, &token_id);
     remove_from_leaderboard(&mut deps, &token_id)?;
 
@@ -1299,16 +2234,30 @@ fn execute_recycle(
         PLAYER_INFO.save(deps.storage, info.sender.as_str(), &player_info)?;
     }
 
-    // 3. Update Stats (Supply decreases, Price drops)
+    // 4. Compost Refund: a share of the current mint price, paid by the treasury
     let mut stats = GAME_STATS.load(deps.storage)?;
+    let price = config
+        .mint_pricing
+        .price(stats.total_minted.saturating_sub(stats.total_burned))?;
+    let mut treasury = TREASURY.may_load(deps.storage)?.unwrap_or_default();
+    let compost_refund = config.rules.compost_refund(price).min(treasury.balance);
+    if !compost_refund.is_zero() {
+        treasury.balance -= compost_refund;
+        treasury.total_composted = treasury.total_composted.checked_add(compost_refund)?;
+        TREASURY.save(deps.storage, &treasury)?;
+    }
+
+    // 5. Update Stats (Supply decreases, Price drops)
     stats.total_burned += 1;
     // Optional: stats.total_recycled += 1;
+    stats.total_rewards_distributed += payout_amount;
+    stats.total_rewards_recycled += forfeited_amount;
     GAME_STATS.save(deps.storage, &stats)?;
 
-    BIOMASS.save(deps.storage, &biomass)?;
+    save_biomass(deps.storage, env.block.time, &biomass)?;
     GLOBAL_STATE.save(deps.storage, &global_state)?;
 
-    // 4. Burn the NFT (Cross-contract call)
+    // 6. Burn the NFT (Cross-contract call)
     // This works because the Game Contract IS the minter/admin
     let burn_msg = WasmMsg::Execute {
         contract_addr: config.cw721_addr.to_string(),
@@ -1318,10 +2267,20 @@ fn execute_recycle(
         funds: vec![],
     };
 
-    Ok(Response::new()
-        .add_message(burn_msg)
+    let mut response = Response::new().add_message(burn_msg);
+    let total_payout = payout_amount + compost_refund;
+    if !total_payout.is_zero() {
+        response = response.add_message(send_payment(&config.payment, &info.sender, total_payout)?);
+    }
+
+    Ok(response
         .add_attribute("action", "recycle")
-        .add_attribute("token_id", token_id))
+        .add_attribute("token_id", token_id)
+        .add_attribute("base_payout", pending)
+        .add_attribute("canopy_multiplier", multiplier.to_string())
+        .add_attribute("final_payout", payout_amount)
+        .add_attribute("recycled_amount", forfeited_amount)
+        .add_attribute("compost_refund", compost_refund))
 }
 
 fn load_and_verify_nft(
@@ -1356,6 +2315,7 @@ fn load_and_verify_nft(
 
 // Logic to add a mushroom's stats to the global counters
 fn add_stats_to_globals(
+    rules: &GameRules,
     biomass: &mut GlobalBiomass,
     global_state: &mut GlobalState,
     traits: &TraitExtension,
@@ -1364,15 +2324,17 @@ fn add_stats_to_globals(
     biomass.total_base_stem += traits.base_stem as u128;
     biomass.total_base_spores += traits.base_spores as u128;
 
-    let shares = calculate_shares(traits);
+    let shares = calculate_shares(rules, traits);
     global_state.total_shares += shares;
 }
 
-// Logic to remove a mushroom's stats from the global counters
+// Logic to remove a mushroom's stats from the global counters. `shares` is what
+// the token holds, which differs from its traits' value after a rules change.
 fn remove_stats_from_globals(
     biomass: &mut GlobalBiomass,
     global_state: &mut GlobalState,
     traits: &TraitExtension,
+    shares: Uint128,
 ) {
     biomass.total_base_cap = biomass
         .total_base_cap
@@ -1384,16 +2346,11 @@ fn remove_stats_from_globals(
         .total_base_spores
         .saturating_sub(traits.base_spores as u128);
 
-    let shares = calculate_shares(traits);
     global_state.total_shares = global_state.total_shares.saturating_sub(shares);
 }
 
-fn count_primordial(genes: &[u8]) -> usize {
-    genes.iter().filter(|&&g| g == 3).count() // Assuming 3 is Primordial ID based on your parse_traits
-}
-
 #[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetPendingSpin { token_id } => {
             to_json_binary(&query_pending_spin(deps, token_id)?)
@@ -1404,11 +2361,12 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to_json_binary(&TOKEN_INFO.may_load(deps.storage, &token_id)?)
         }
         QueryMsg::GetPendingRewards { token_id } => {
-            to_json_binary(&query_pending_rewards(deps, token_id)?)
+            to_json_binary(&query_pending_rewards(deps, env, token_id)?)
         }
-        QueryMsg::GetEcosystemMetrics {} => to_json_binary(&query_ecosystem_metrics(deps)?),
+        QueryMsg::GetEcosystemMetrics {} => to_json_binary(&query_ecosystem_metrics(deps, env)?),
         QueryMsg::GetGameStats {} => to_json_binary(&query_game_stats(deps)?),
         QueryMsg::GetCurrentMintPrice {} => to_json_binary(&query_current_mint_price(deps)?),
+        QueryMsg::QuoteMint { quantity } => to_json_binary(&query_quote_mint(deps, quantity)?),
         QueryMsg::GetPlayerProfile { address } => {
             to_json_binary(&query_player_profile(deps, address)?)
         }
@@ -1421,10 +2379,100 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::GetPendingAscend { token_id } => {
             to_json_binary(&query_pending_ascend(deps, token_id)?)
         }
+        QueryMsg::GetPendingGeneEdit { token_id } => {
+            to_json_binary(&query_pending_gene_edit(deps, token_id)?)
+        }
         QueryMsg::GetLeaderboard {} => to_json_binary(&query_leaderboard(deps)?),
+        QueryMsg::AccruedFees {} => to_json_binary(&query_accrued_fees(deps)?),
+        QueryMsg::EmissionSchedule {} => to_json_binary(&query_emission_schedule(deps, env)?),
+        QueryMsg::SunLine {} => to_json_binary(&query_sun_line(deps, env)?),
+        QueryMsg::EcosystemHistory { start_after, limit } => {
+            to_json_binary(&query_ecosystem_history(deps, start_after, limit)?)
+        }
+        QueryMsg::GameRules {} => to_json_binary(&CONFIG.load(deps.storage)?.rules),
+        QueryMsg::PreviewSplice {
+            parent_1_id,
+            parent_2_id,
+        } => to_json_binary(&query_preview_splice(deps, parent_1_id, parent_2_id)?),
+        QueryMsg::SimulateSpin {
+            token_id,
+            trait_target,
+        } => to_json_binary(&query_simulate_spin(deps, env, token_id, trait_target)?),
+        QueryMsg::SimulateAscend { token_id } => {
+            to_json_binary(&query_simulate_ascend(deps, env, token_id)?)
+        }
+        QueryMsg::Lineage { token_id, depth } => {
+            to_json_binary(&query_lineage(deps, token_id, depth)?)
+        }
+        QueryMsg::Descendants { token_id } => to_json_binary(&DescendantsResponse {
+            descendants: lineage::descendants(deps.storage, &token_id)?,
+        }),
     }
 }
 
+fn query_ecosystem_history(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<EcosystemHistoryResponse> {
+    let limit = limit
+        .unwrap_or(DEFAULT_HISTORY_LIMIT)
+        .min(MAX_HISTORY_LIMIT) as usize;
+    let snapshots = ECOSYSTEM_HISTORY
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| item.map(|(_, snapshot)| snapshot))
+        .collect::<StdResult<_>>()?;
+    Ok(EcosystemHistoryResponse { snapshots })
+}
+
+fn query_sun_line(deps: Deps, env: Env) -> StdResult<SunLineResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let epoch = match current_canopy_epoch(deps.storage, env.block.time)? {
+        Some(epoch) => epoch,
+        None => snapshot_sun_line(deps.storage, 0, env.block.time)?,
+    };
+    Ok(SunLineResponse {
+        epoch: epoch.epoch,
+        started_at: epoch.started_at,
+        next_epoch_at: epoch.started_at.plus_seconds(config.epoch_length),
+        sun_line: epoch.sun_line,
+        shadow_line: epoch.sun_line * config.rules.shadow_zone,
+        active_mushrooms: epoch.active_mushrooms,
+    })
+}
+
+fn query_emission_schedule(deps: Deps, env: Env) -> StdResult<EmissionScheduleResponse> {
+    let emissions = EMISSIONS
+        .may_load(deps.storage)?
+        .unwrap_or_else(|| Emissions::new(env.block.time));
+    Ok(EmissionScheduleResponse {
+        current_rate: emissions.schedule.rate_at(env.block.time),
+        schedule: emissions.schedule,
+        reserve: emissions.reserve,
+        total_emitted: emissions.total_emitted,
+        last_accrued: emissions.last_accrued,
+    })
+}
+
+fn query_accrued_fees(deps: Deps) -> StdResult<AccruedFeesResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let treasury = TREASURY.may_load(deps.storage)?.unwrap_or_default();
+    Ok(AccruedFeesResponse {
+        fee_split: config.fee_split,
+        treasury_balance: treasury.balance,
+        total_accrued: treasury.total_accrued,
+        total_withdrawn: treasury.total_withdrawn,
+        total_burned: treasury.total_burned,
+        total_composted: treasury.total_composted,
+    })
+}
+
 fn query_leaderboard(deps: Deps) -> StdResult<LeaderboardResponse> {
     let entries = LEADERBOARD.load(deps.storage).unwrap_or_default();
     Ok(LeaderboardResponse { entries })
@@ -1443,37 +2491,278 @@ fn query_pending_mint(deps: Deps, mint_id: String) -> StdResult<PendingMintRespo
     }
 }
 
-fn query_pending_splice(deps: Deps, splice_id: String) -> StdResult<PendingSpliceResponse> {
-    match PENDING_SPLICES.may_load(deps.storage, &splice_id)? {
-        Some(pending) => Ok(PendingSpliceResponse {
-            is_pending: true,
-            target_round: pending.target_round,
-        }),
-        None => Ok(PendingSpliceResponse {
-            is_pending: false,
-            target_round: 0,
-        }),
-    }
+fn query_preview_splice(
+    deps: Deps,
+    parent_1_id: String,
+    parent_2_id: String,
+) -> StdResult<SplicePreviewResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    preview_splice(
+        &config.rules,
+        &query_traits(deps, &config, &parent_1_id)?,
+        &query_traits(deps, &config, &parent_2_id)?,
+    )
 }
 
-fn query_pending_ascend(deps: Deps, token_id: String) -> StdResult<PendingAscendResponse> {
-    match PENDING_ASCENDS.may_load(deps.storage, &token_id)? {
-        Some(pending) => Ok(PendingAscendResponse {
-            is_pending: true,
-            target_round: pending.target_round,
-        }),
-        None => Ok(PendingAscendResponse {
-            is_pending: false,
-            target_round: 0,
-        }),
+fn query_traits(deps: Deps, config: &GameConfig, token_id: &str) -> StdResult<TraitExtension> {
+    let nft_info: cw721::msg::NftInfoResponse<NftExtensionMsg> = deps.querier.query_wasm_smart(
+        config.cw721_addr.to_string(),
+        &cw721::msg::Cw721QueryMsg::<NftExtensionMsg, Empty, Empty>::NftInfo {
+            token_id: token_id.to_string(),
+        },
+    )?;
+    Ok(parse_traits(nft_info.extension))
+}
+
+/// Shares and emission rate of `token_id` now, and on average over `outcomes`
+/// weighted out of `total_weight`
+fn estimate_shares(
+    deps: Deps,
+    env: &Env,
+    config: &GameConfig,
+    token_id: &str,
+    traits: &TraitExtension,
+    outcomes: &[(TraitExtension, u128)],
+    total_weight: u128,
+) -> StdResult<ShareEstimate> {
+    let global_state = GLOBAL_STATE.load(deps.storage)?;
+    let current_shares = match TOKEN_INFO.may_load(deps.storage, token_id)? {
+        Some(info) => info.current_shares,
+        None => calculate_shares(&config.rules, traits),
+    };
+    let emission_rate = EMISSIONS
+        .may_load(deps.storage)?
+        .map_or(Uint128::zero(), |emissions| {
+            emissions.schedule.rate_at(env.block.time)
+        });
+    let biomass = time_weighted_biomass(deps.storage, env.block.time)?;
+    let sun_line = current_canopy_epoch(deps.storage, env.block.time)?
+        .map_or(Decimal::zero(), |epoch| epoch.sun_line);
+
+    // Everyone else's shares stay as they are
+    let other_shares = global_state.total_shares.saturating_sub(current_shares);
+    let reward_rate = |traits: &TraitExtension, shares: Uint128| -> StdResult<Decimal> {
+        let total_shares = other_shares.checked_add(shares)?;
+        if total_shares.is_zero() {
+            return Ok(Decimal::zero());
+        }
+        let rate = Decimal::checked_from_ratio(emission_rate.checked_mul(shares)?, total_shares)
+            .map_err(|e| StdError::generic_err(e.to_string()))?;
+        Ok(rate * calculate_canopy_multiplier(&config.rules, &biomass, traits, sun_line))
+    };
+
+    let mut expected_shares = Decimal::zero();
+    let mut expected_reward_rate = Decimal::zero();
+    for (outcome, weight) in outcomes {
+        let chance = Decimal::from_ratio(*weight, total_weight);
+        let shares = calculate_shares(&config.rules, outcome);
+        expected_shares += Decimal::from_ratio(shares, 1u128) * chance;
+        expected_reward_rate += reward_rate(outcome, shares)? * chance;
     }
+
+    let signed = |value: Decimal| {
+        SignedDecimal::try_from(value).map_err(|e| StdError::generic_err(e.to_string()))
+    };
+    Ok(ShareEstimate {
+        current_shares,
+        expected_shares,
+        expected_share_change: signed(expected_shares)?
+            - signed(Decimal::from_ratio(current_shares, 1u128))?,
+        current_reward_rate: reward_rate(traits, current_shares)?,
+        expected_reward_rate,
+    })
 }
 
-fn query_pending_spin(deps: Deps, token_id: String) -> StdResult<PendingSpinResponse> {
-    match PENDING_SPINS.may_load(deps.storage, &token_id)? {
-        Some(pending) => Ok(PendingSpinResponse {
-            is_pending: true,
-            target_round: pending.target_round,
+fn query_simulate_spin(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+    trait_target: TraitTarget,
+) -> StdResult<SpinSimulationResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let traits = query_traits(deps, &config, &token_id)?;
+
+    // Every value the resolver's random byte can take
+    let mut values: BTreeMap<i8, u128> = BTreeMap::new();
+    let mut wins = 0u128;
+    for random_value in 0..=u8::MAX {
+        let spin = spin_result(&config.rules, &traits, &trait_target, random_value);
+        if spin.success {
+            wins += 1;
+        }
+        *values.entry(spin.new_value).or_default() += 1;
+    }
+    let byte_outcomes = u8::MAX as u128 + 1;
+
+    let outcomes: Vec<(TraitExtension, u128)> = values
+        .iter()
+        .map(|(&value, &weight)| {
+            let mut outcome = traits.clone();
+            set_trait_value(&mut outcome, &trait_target, value);
+            (outcome, weight)
+        })
+        .collect();
+    let estimate = estimate_shares(
+        deps,
+        &env,
+        &config,
+        &token_id,
+        &traits,
+        &outcomes,
+        byte_outcomes,
+    )?;
+
+    let cost = config.spin_cost.checked_mul(Uint128::from(
+        config.rules.spin_cost_multiplier(traits.substrate),
+    ))?;
+
+    Ok(SpinSimulationResponse {
+        current_value: trait_value(&traits, &trait_target).0,
+        success_chance: Decimal::from_ratio(wins, byte_outcomes),
+        outcomes: values
+            .into_iter()
+            .map(|(value, weight)| TraitValueOdds {
+                value,
+                chance: Decimal::from_ratio(weight, byte_outcomes),
+            })
+            .collect(),
+        cost,
+        estimate,
+    })
+}
+
+fn query_simulate_ascend(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+) -> StdResult<AscendSimulationResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let traits = query_traits(deps, &config, &token_id)?;
+
+    if traits.cap != 3 || traits.stem != 3 || traits.spores != 3 {
+        return Err(StdError::generic_err(
+            ContractError::NotMaxLevel {}.to_string(),
+        ));
+    }
+    if traits.substrate >= MAX_ASCEND_SUBSTRATE {
+        return Err(StdError::generic_err(
+            ContractError::MaxSubstrate {}.to_string(),
+        ));
+    }
+
+    // A success burns everything pending, a failure its configured share
+    let global_state = GLOBAL_STATE.load(deps.storage)?;
+    let (attempts, cost) = match TOKEN_INFO.may_load(deps.storage, &token_id)? {
+        Some(info) => {
+            let accrued = accrued_rewards(&info, global_state.global_reward_index)?;
+            (
+                info.ascension_attempts,
+                info.pending_rewards.checked_add(accrued)?,
+            )
+        }
+        None => (0, Uint128::zero()),
+    };
+
+    let chance = config.rules.ascend_chance(attempts) as u128;
+    let reset = TraitExtension {
+        cap: 0,
+        stem: 0,
+        spores: 0,
+        ..traits.clone()
+    };
+    let ascended = TraitExtension {
+        substrate: traits.substrate + 1,
+        ..reset.clone()
+    };
+    let failed = if config.rules.ascend_failure_resets_stats {
+        reset
+    } else {
+        traits.clone()
+    };
+    let outcomes: Vec<(TraitExtension, u128)> =
+        [(failed, BPS as u128 - chance), (ascended, chance)]
+            .into_iter()
+            .filter(|(_, weight)| *weight > 0)
+            .collect();
+    let estimate = estimate_shares(
+        deps,
+        &env,
+        &config,
+        &token_id,
+        &traits,
+        &outcomes,
+        BPS as u128,
+    )?;
+
+    Ok(AscendSimulationResponse {
+        current_substrate: traits.substrate,
+        success_chance: Decimal::from_ratio(chance, BPS as u128),
+        outcomes: outcomes
+            .iter()
+            .map(|(outcome, weight)| SubstrateOdds {
+                substrate: outcome.substrate,
+                chance: Decimal::from_ratio(*weight, BPS as u128),
+            })
+            .collect(),
+        failure_cost: config.rules.ascend_failure_burn(cost),
+        cost,
+        estimate,
+    })
+}
+
+fn query_lineage(deps: Deps, token_id: String, depth: Option<u32>) -> StdResult<LineageResponse> {
+    let depth = depth
+        .unwrap_or(DEFAULT_LINEAGE_DEPTH)
+        .min(MAX_LINEAGE_DEPTH);
+    let record =
+        lineage::load(deps.storage, &token_id)?.ok_or_else(|| StdError::not_found("lineage"))?;
+    Ok(LineageResponse {
+        ancestors: lineage::ancestors(deps.storage, &token_id, depth)?,
+        token_id,
+        record,
+    })
+}
+
+fn query_pending_splice(deps: Deps, splice_id: String) -> StdResult<PendingSpliceResponse> {
+    match PENDING_SPLICES.may_load(deps.storage, &splice_id)? {
+        Some(pending) => Ok(PendingSpliceResponse {
+            is_pending: true,
+            target_round: pending.target_round,
+        }),
+        None => Ok(PendingSpliceResponse {
+            is_pending: false,
+            target_round: 0,
+        }),
+    }
+}
+
+fn query_pending_ascend(deps: Deps, token_id: String) -> StdResult<PendingAscendResponse> {
+    let rules = CONFIG.load(deps.storage)?.rules;
+    let ascension_attempts = TOKEN_INFO
+        .may_load(deps.storage, &token_id)?
+        .map_or(0, |info| info.ascension_attempts);
+    let success_chance_bps = rules.ascend_chance(ascension_attempts);
+    match PENDING_ASCENDS.may_load(deps.storage, &token_id)? {
+        Some(pending) => Ok(PendingAscendResponse {
+            is_pending: true,
+            target_round: pending.target_round,
+            ascension_attempts,
+            success_chance_bps,
+        }),
+        None => Ok(PendingAscendResponse {
+            is_pending: false,
+            target_round: 0,
+            ascension_attempts,
+            success_chance_bps,
+        }),
+    }
+}
+
+fn query_pending_spin(deps: Deps, token_id: String) -> StdResult<PendingSpinResponse> {
+    match PENDING_SPINS.may_load(deps.storage, &token_id)? {
+        Some(pending) => Ok(PendingSpinResponse {
+            is_pending: true,
+            target_round: pending.target_round,
         }),
         None => Ok(PendingSpinResponse {
             is_pending: false,
@@ -1482,6 +2771,21 @@ fn query_pending_spin(deps: Deps, token_id: String) -> StdResult<PendingSpinResp
     }
 }
 
+fn query_pending_gene_edit(deps: Deps, token_id: String) -> StdResult<PendingGeneEditResponse> {
+    match PENDING_GENE_EDITS.may_load(deps.storage, &token_id)? {
+        Some(pending) => Ok(PendingGeneEditResponse {
+            is_pending: true,
+            target_round: pending.target_round,
+            slot: pending.slot,
+        }),
+        None => Ok(PendingGeneEditResponse {
+            is_pending: false,
+            target_round: 0,
+            slot: 0,
+        }),
+    }
+}
+
 fn query_player_profile(deps: Deps, address: String) -> StdResult<PlayerProfileResponse> {
     let player = match PLAYER_INFO.may_load(deps.storage, &address)? {
         Some(p) => p,
@@ -1511,16 +2815,8 @@ fn query_player_profile(deps: Deps, address: String) -> StdResult<PlayerProfileR
                 best_id = Some(token_id.clone());
             }
 
-            let mut pending = info.pending_rewards;
-            if !info.current_shares.is_zero() {
-                let accrued = info
-                    .current_shares
-                    .checked_mul(global_state.global_reward_index)?
-                    .checked_sub(info.reward_debt)
-                    .unwrap_or(Uint128::zero());
-                pending += accrued;
-            }
-            total_rewards += pending;
+            let accrued = accrued_rewards(&info, global_state.global_reward_index)?;
+            total_rewards += info.pending_rewards + accrued;
         }
     }
 
@@ -1537,12 +2833,35 @@ fn query_current_mint_price(deps: Deps) -> StdResult<MintPriceResponse> {
     let stats = GAME_STATS.load(deps.storage)?;
 
     let current_supply = stats.total_minted.saturating_sub(stats.total_burned);
-    let increment_total = config.mint_cost_increment * Uint128::from(current_supply);
-    let price = config.mint_cost + increment_total;
+    let price = config.mint_pricing.price(current_supply)?;
 
     Ok(MintPriceResponse { price })
 }
 
+fn query_quote_mint(deps: Deps, quantity: u64) -> StdResult<MintQuoteResponse> {
+    if quantity == 0 || quantity > MAX_QUOTE_QUANTITY {
+        return Err(StdError::generic_err(format!(
+            "quantity must be between 1 and {}",
+            MAX_QUOTE_QUANTITY
+        )));
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let stats = GAME_STATS.load(deps.storage)?;
+
+    let current_supply = stats.total_minted.saturating_sub(stats.total_burned);
+    let prices = config.mint_pricing.quote(current_supply, quantity)?;
+    let total_cost = prices
+        .iter()
+        .try_fold(Uint128::zero(), |total, price| total.checked_add(*price))?;
+
+    Ok(MintQuoteResponse {
+        quantity,
+        total_cost,
+        prices,
+    })
+}
+
 fn query_game_stats(deps: Deps) -> StdResult<GameStatsResponse> {
     let stats = GAME_STATS.load(deps.storage)?;
     let biomass = BIOMASS.load(deps.storage)?;
@@ -1562,12 +2881,17 @@ fn query_game_stats(deps: Deps) -> StdResult<GameStatsResponse> {
         total_harvests: stats.total_harvests,
         total_splices: stats.total_splices,
         total_ascensions: stats.total_ascensions,
+        total_gene_edits: stats.total_gene_edits,
     })
 }
 
-fn query_pending_rewards(deps: Deps, token_id: String) -> StdResult<PendingRewardsResponse> {
-    let global_state = GLOBAL_STATE.load(deps.storage)?;
-    let biomass = BIOMASS.load(deps.storage)?;
+fn query_pending_rewards(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+) -> StdResult<PendingRewardsResponse> {
+    let mut global_state = GLOBAL_STATE.load(deps.storage)?;
+    let biomass = time_weighted_biomass(deps.storage, env.block.time)?;
     let config = CONFIG.load(deps.storage)?;
 
     let token_info = match TOKEN_INFO.may_load(deps.storage, &token_id)? {
@@ -1577,27 +2901,29 @@ fn query_pending_rewards(deps: Deps, token_id: String) -> StdResult<PendingRewar
                 accumulated_rewards: Uint128::zero(),
                 canopy_multiplier: Decimal::one(),
                 estimated_payout: Uint128::zero(),
+                projected_emissions: Uint128::zero(),
             })
         }
     };
 
-    // 1. Calculate Raw Accumulated (Standard Staking Math)
-    let mut raw_pending = token_info.pending_rewards;
-
-    if !token_info.current_shares.is_zero() {
-        let accumulated_since_last_interaction = token_info
-            .current_shares
-            .checked_mul(global_state.global_reward_index)?
-            .checked_sub(token_info.reward_debt)
-            .unwrap_or(Uint128::zero());
-        raw_pending = raw_pending.checked_add(accumulated_since_last_interaction)?;
+    // 0. Project emissions released since the last execute, as the next one would accrue them
+    let accrued_before = accrued_rewards(&token_info, global_state.global_reward_index)?;
+    if let Some(mut emissions) = EMISSIONS.may_load(deps.storage)? {
+        let released = emissions.accrue(global_state.total_shares, env.block.time)?;
+        distribute_rewards(&mut global_state, released)?;
     }
+    let accrued = accrued_rewards(&token_info, global_state.global_reward_index)?;
+    let projected_emissions = accrued - accrued_before;
+
+    // 1. Calculate Raw Accumulated (Standard Staking Math)
+    let raw_pending = token_info.pending_rewards.checked_add(accrued)?;
 
     if raw_pending.is_zero() {
         return Ok(PendingRewardsResponse {
             accumulated_rewards: Uint128::zero(),
             canopy_multiplier: Decimal::one(),
             estimated_payout: Uint128::zero(),
+            projected_emissions: Uint128::zero(),
         });
     }
 
@@ -1610,7 +2936,9 @@ fn query_pending_rewards(deps: Deps, token_id: String) -> StdResult<PendingRewar
     )?;
     let traits = parse_traits(nft_info.extension);
 
-    let multiplier = calculate_canopy_multiplier(&biomass, &traits);
+    let sun_line = current_canopy_epoch(deps.storage, env.block.time)?
+        .map_or(Decimal::zero(), |epoch| epoch.sun_line);
+    let multiplier = calculate_canopy_multiplier(&config.rules, &biomass, &traits, sun_line);
 
     // 3. Calculate Final Payout
     let final_payout = raw_pending.mul_floor(multiplier);
@@ -1619,85 +2947,90 @@ fn query_pending_rewards(deps: Deps, token_id: String) -> StdResult<PendingRewar
         accumulated_rewards: raw_pending,
         canopy_multiplier: multiplier,
         estimated_payout: final_payout,
+        projected_emissions,
     })
 }
 
-fn calculate_canopy_multiplier(biomass: &GlobalBiomass, traits: &TraitExtension) -> Decimal {
+/// Scarcity multiplier of a trait: its target share of the biomass over its
+/// actual share
+fn trait_multiplier(rules: &GameRules, biomass: &GlobalBiomass, target: &TraitTarget) -> Decimal {
     let total_mass = biomass.total_base_cap + biomass.total_base_stem + biomass.total_base_spores;
-
-    // If ecosystem is empty or mushroom has no genes, return standard 1.0 (or 0 if no genes)
     if total_mass == 0 {
         return Decimal::one();
     }
-    let user_base_total = traits.base_cap + traits.base_stem + traits.base_spores;
-    if user_base_total == 0 {
-        return Decimal::one();
+    let trait_mass = match target {
+        TraitTarget::Cap => biomass.total_base_cap,
+        TraitTarget::Stem => biomass.total_base_stem,
+        TraitTarget::Spores => biomass.total_base_spores,
+    };
+    if trait_mass == 0 {
+        // If this trait doesn't exist in the pool yet, it is infinitely valuable.
+        // Capped to prevent exploits.
+        return rules.max_scarcity_multiplier;
     }
+    let target_ratio = rules.target_share(target);
+    let actual_ratio = Decimal::from_ratio(trait_mass, total_mass);
+    target_ratio / actual_ratio
+}
 
-    // Target is perfect equilibrium (33% split)
-    let target_ratio = Decimal::from_ratio(1u128, 3u128); // 0.333...
-
-    // Helper to get multiplier for a specific trait type
-    let get_trait_mult = |trait_mass: u128| -> Decimal {
-        if trait_mass == 0 {
-            // If this trait doesn't exist in the pool yet, it is infinitely valuable.
-            // Cap at 5x to prevent exploits.
-            Decimal::from_str("5.0").unwrap()
-        } else {
-            let actual_ratio = Decimal::from_ratio(trait_mass, total_mass);
-            // Multiplier = Target / Actual
-            target_ratio / actual_ratio
-        }
+/// Score = (Base * Mult) + ... for each trait
+fn canopy_score(rules: &GameRules, biomass: &GlobalBiomass, traits: &TraitExtension) -> Decimal {
+    let score = |base: u8, target: TraitTarget| {
+        Decimal::from_ratio(base, 1u128) * trait_multiplier(rules, biomass, &target)
     };
+    score(traits.base_cap, TraitTarget::Cap)
+        + score(traits.base_stem, TraitTarget::Stem)
+        + score(traits.base_spores, TraitTarget::Spores)
+}
 
-    let cap_mult = get_trait_mult(biomass.total_base_cap);
-    let stem_mult = get_trait_mult(biomass.total_base_stem);
-    let spores_mult = get_trait_mult(biomass.total_base_spores);
-
-    // Calculate User's "Canopy Score"
-    // Score = (Base * Mult) + ...
-    let score_cap = Decimal::from_ratio(traits.base_cap, 1u128) * cap_mult;
-    let score_stem = Decimal::from_ratio(traits.base_stem, 1u128) * stem_mult;
-    let score_spores = Decimal::from_ratio(traits.base_spores, 1u128) * spores_mult;
-
-    let total_score = score_cap + score_stem + score_spores;
+/// Sum of the canopy scores of all active mushrooms. Scores are linear in base
+/// stats, so the sum follows exactly from the biomass totals, which every mint,
+/// splice, ascension and burn already keeps up to date.
+fn total_canopy_score(rules: &GameRules, biomass: &GlobalBiomass) -> Decimal {
+    let score = |mass: u128, target: TraitTarget| {
+        Decimal::from_ratio(mass, 1u128) * trait_multiplier(rules, biomass, &target)
+    };
+    score(biomass.total_base_cap, TraitTarget::Cap)
+        + score(biomass.total_base_stem, TraitTarget::Stem)
+        + score(biomass.total_base_spores, TraitTarget::Spores)
+}
 
-    // Calculate Efficiency (Score / Raw Stats)
-    let efficiency = total_score / Decimal::from_ratio(user_base_total, 1u128);
+fn calculate_canopy_multiplier(
+    rules: &GameRules,
+    biomass: &GlobalBiomass,
+    traits: &TraitExtension,
+    sun_line: Decimal,
+) -> Decimal {
+    let total_score = canopy_score(rules, biomass, traits);
 
     // Shadow Zone Logic:
-    // If your efficiency is below 0.8 (meaning you are mostly composed of oversaturated traits),
-    // you are in the shadow and earn 0.
-    if efficiency < Decimal::from_str("0.8").unwrap() {
+    // Mushrooms scoring below the shadow zone fraction of this epoch's Sun Line (the
+    // average score of all active mushrooms) are choked out and earn 0.
+    if total_score < sun_line * rules.shadow_zone {
         return Decimal::zero();
     }
 
-    // Otherwise, return the efficiency as the reward multiplier
-    efficiency
+    // If ecosystem is empty or mushroom has no genes, return standard 1.0
+    let total_mass = biomass.total_base_cap + biomass.total_base_stem + biomass.total_base_spores;
+    let user_base_total = traits.base_cap + traits.base_stem + traits.base_spores;
+    if total_mass == 0 || user_base_total == 0 {
+        return Decimal::one();
+    }
+
+    // Otherwise, return the efficiency (Score / Raw Stats) as the reward multiplier
+    total_score / Decimal::from_ratio(user_base_total, 1u128)
 }
 
-fn query_ecosystem_metrics(deps: Deps) -> StdResult<EcosystemMetricsResponse> {
+fn query_ecosystem_metrics(deps: Deps, env: Env) -> StdResult<EcosystemMetricsResponse> {
+    let rules = CONFIG.load(deps.storage)?.rules;
     let biomass = BIOMASS.load(deps.storage)?;
 
-    let total_mass = biomass.total_base_cap + biomass.total_base_stem + biomass.total_base_spores;
-    let target_ratio = Decimal::from_ratio(1u128, 3u128);
-
-    let calc_mult = |mass: u128| -> Decimal {
-        if total_mass == 0 {
-            return Decimal::one();
-        }
-        if mass == 0 {
-            return Decimal::from_str("5.0").unwrap();
-        }
-        let actual_ratio = Decimal::from_ratio(mass, total_mass);
-        target_ratio / actual_ratio
-    };
-
     Ok(EcosystemMetricsResponse {
-        total_biomass: biomass.clone(),
-        cap_multiplier: calc_mult(biomass.total_base_cap),
-        stem_multiplier: calc_mult(biomass.total_base_stem),
-        spores_multiplier: calc_mult(biomass.total_base_spores),
+        time_weighted_biomass: time_weighted_biomass(deps.storage, env.block.time)?,
+        cap_multiplier: trait_multiplier(&rules, &biomass, &TraitTarget::Cap),
+        stem_multiplier: trait_multiplier(&rules, &biomass, &TraitTarget::Stem),
+        spores_multiplier: trait_multiplier(&rules, &biomass, &TraitTarget::Spores),
+        total_biomass: biomass,
     })
 }
 
@@ -1712,7 +3045,7 @@ fn update_leaderboard(deps: &mut DepsMut, token_id: String, score: Uint128) -> S
     leaderboard.push(LeaderboardEntry { token_id, score });
 
     // 3. Sort Descending by Score
-    leaderboard.sort_by(|a, b| b.score.cmp(&a.score));
+    leaderboard.sort_by_key(|entry| std::cmp::Reverse(entry.score));
 
     // 4. Keep only Top 10
     if leaderboard.len() > 10 {
@@ -1738,21 +3071,57 @@ fn remove_from_leaderboard(deps: &mut DepsMut, token_id: &String) -> StdResult<(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fees::FeeSplit;
+    use crate::rules::SpliceMutation;
+    use crate::state::{LegacyGameConfig, Treasury};
     use cosmwasm_std::testing::{message_info, mock_env, MockApi, MockQuerier, MockStorage};
     use cosmwasm_std::{
-        coins, from_json, Addr, ContractResult, OwnedDeps, SystemError, SystemResult,
-        WasmQuery as CosmWasmQuery,
+        coins, from_json, Addr, ContractResult, OwnedDeps, QuerierResult, SystemError,
+        SystemResult, WasmQuery as CosmWasmQuery,
     };
+    use cw20::UncheckedDenom;
     use cw721::msg::{NftInfoResponse, OwnerOfResponse};
     use cw721::state::Trait;
+    use spore_fates::cw721::MAX_SUBSTRATE;
+    use std::str::FromStr;
 
     const PAYMENT_DENOM: &str = "factory/creator/shroom";
 
-    /// Returns a mock env with block time after drand genesis (required for round calculation)
-    fn mock_env_drand() -> cosmwasm_std::Env {
-        let mut env = mock_env();
-        env.block.time = cosmwasm_std::Timestamp::from_seconds(DRAND_GENESIS + 30);
-        env
+    /// Latest drand round public at `mock_env()` block time
+    const LATEST_ROUND: u64 = 10;
+
+    /// Answers the drand oracle queries made by the controller. Rounds are 3 seconds
+    /// apart, with `LATEST_ROUND` published exactly at `mock_env()` block time.
+    fn mock_oracle_query(msg: &Binary) -> QuerierResult {
+        let now = mock_env().block.time.seconds() as i64;
+        let round_response = |round: u64| {
+            let time = now + (round as i64 - LATEST_ROUND as i64) * 3;
+            SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&RoundResponse {
+                    round: Uint64::new(round),
+                    time: Timestamp::from_seconds(time as u64),
+                })
+                .unwrap(),
+            ))
+        };
+
+        match from_json(msg) {
+            Ok(OracleQueryMsg::RoundAt { time }) => {
+                let elapsed = time.seconds() as i64 - now;
+                round_response((LATEST_ROUND as i64 + elapsed.div_euclid(3)) as u64)
+            }
+            Ok(OracleQueryMsg::TimeOf { round }) => round_response(round.u64()),
+            Ok(OracleQueryMsg::Beacon { .. }) => SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&BeaconResponse {
+                    uniform_seed: [123u8; 32],
+                })
+                .unwrap(),
+            )),
+            Err(e) => SystemResult::Err(SystemError::InvalidRequest {
+                error: format!("Parse error: {}", e),
+                request: msg.clone(),
+            }),
+        }
     }
 
     // Setup custom mocks using MockApi to generate valid Bech32 addresses
@@ -1772,12 +3141,22 @@ mod tests {
         oracle: &Addr,
     ) -> Result<Response, ContractError> {
         let msg = InstantiateMsg {
-            payment_denom: PAYMENT_DENOM.to_string(),
+            payment: UncheckedDenom::Native(PAYMENT_DENOM.to_string()),
             spin_cost: Uint128::new(1_000_000),
-            mint_cost: Uint128::new(0),
-            mint_cost_increment: Uint128::new(0),
+            mint_pricing: PricingCurve::Linear {
+                base: Uint128::zero(),
+                increment: Uint128::zero(),
+            },
             cw721_addr: cw721.to_string(),
             oracle_addr: oracle.to_string(),
+            min_round_delay: None,
+            max_block_time: None,
+            admin: None,
+            fee_split: None,
+            burn_addr: None,
+            epoch_length: None,
+            history_retention: None,
+            rules: None,
         };
         let info = message_info(creator, &[]);
         instantiate(deps, mock_env(), info, msg)
@@ -1862,9 +3241,7 @@ mod tests {
                         }),
                     }
                 } else {
-                    SystemResult::Err(SystemError::UnsupportedRequest {
-                        kind: "Unknown contract".to_string(),
-                    })
+                    mock_oracle_query(msg)
                 }
             }
             _ => SystemResult::Err(SystemError::UnsupportedRequest {
@@ -1891,7 +3268,7 @@ mod tests {
         let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
         let config: GameConfig = from_json(&res).unwrap();
 
-        assert_eq!(config.payment_denom, PAYMENT_DENOM);
+        assert_eq!(config.payment, Denom::Native(PAYMENT_DENOM.to_string()));
         assert_eq!(config.spin_cost, Uint128::new(1_000_000));
 
         // Query global state
@@ -1899,50 +3276,2046 @@ mod tests {
         let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
         let global_state: GlobalState = from_json(&res).unwrap();
 
-        assert_eq!(global_state.total_shares, Uint128::zero());
-        assert_eq!(global_state.global_reward_index, Uint128::zero());
-        assert_eq!(global_state.spin_nonce, 0);
+        assert_eq!(global_state.total_shares, Uint128::zero());
+        assert_eq!(global_state.global_reward_index, Decimal256::zero());
+        assert_eq!(global_state.spin_nonce, 0);
+    }
+
+    #[test]
+    fn test_mint_bonding_curve() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let user = deps.api.addr_make("user");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+
+        // 1. Instantiate
+        let msg = InstantiateMsg {
+            payment: UncheckedDenom::Native(PAYMENT_DENOM.to_string()),
+            spin_cost: Uint128::new(100),
+            mint_pricing: PricingCurve::Linear {
+                base: Uint128::new(100),
+                increment: Uint128::new(10),
+            },
+            oracle_addr: oracle.to_string(),
+            cw721_addr: cw721.to_string(),
+            min_round_delay: None,
+            max_block_time: None,
+            admin: None,
+            fee_split: None,
+            burn_addr: None,
+            epoch_length: None,
+            history_retention: None,
+            rules: None,
+        };
+        let info = message_info(&creator, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        deps.querier.update_wasm(|query| match query {
+            CosmWasmQuery::Smart { msg, .. } => mock_oracle_query(msg),
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "skip".into(),
+            }),
+        });
+        let env = mock_env();
+
+        // 2. Request Mint #1
+        let info = message_info(&user, &coins(100, PAYMENT_DENOM));
+        let res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::RequestMint {}).unwrap();
+
+        assert_eq!(res.attributes[0].value, "request_mint");
+        assert_eq!(res.attributes[1].value, "1"); // mint_id
+
+        // Price hasn't increased yet (happens on resolve, not request)
+        let query_msg = QueryMsg::GetCurrentMintPrice {};
+        let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+        let price_response: MintPriceResponse = from_json(&res).unwrap();
+        assert_eq!(price_response.price, Uint128::new(100));
+
+        // 3. Request Mint #2 (same price since #1 hasn't resolved yet)
+        let info = message_info(&user, &coins(100, PAYMENT_DENOM));
+        let res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::RequestMint {}).unwrap();
+        assert_eq!(res.attributes[1].value, "2"); // mint_id
+    }
+
+    #[test]
+    fn test_fee_split_and_treasury() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let user = deps.api.addr_make("user");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+        let burn = deps.api.addr_make("burn");
+
+        let mut msg = InstantiateMsg {
+            payment: UncheckedDenom::Native(PAYMENT_DENOM.to_string()),
+            spin_cost: Uint128::new(100),
+            mint_pricing: PricingCurve::Linear {
+                base: Uint128::new(1000),
+                increment: Uint128::zero(),
+            },
+            oracle_addr: oracle.to_string(),
+            cw721_addr: cw721.to_string(),
+            min_round_delay: None,
+            max_block_time: None,
+            admin: None,
+            fee_split: Some(FeeSplit {
+                treasury_bps: 1_000,
+                burn_bps: 500,
+                reward_bps: 8_500,
+            }),
+            burn_addr: None,
+            epoch_length: None,
+            history_retention: None,
+            rules: None,
+        };
+
+        // Burning needs somewhere to send the tokens
+        let info = message_info(&creator, &[]);
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidFeeSplit { .. }));
+
+        msg.burn_addr = Some(burn.to_string());
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        deps.querier.update_wasm(|query| match query {
+            CosmWasmQuery::Smart { msg, .. } => mock_oracle_query(msg),
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "skip".into(),
+            }),
+        });
+
+        // Existing holders to receive the reward share
+        let mut global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+        global_state.total_shares = Uint128::new(85);
+        GLOBAL_STATE
+            .save(deps.as_mut().storage, &global_state)
+            .unwrap();
+
+        let info = message_info(&user, &coins(1000, PAYMENT_DENOM));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::RequestMint {}).unwrap();
+        let resolve = ExecuteMsg::ResolveMint {
+            mint_id: "1".to_string(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), message_info(&user, &[]), resolve).unwrap();
+
+        // 10% kept, 5% burned, 85% to the pool
+        assert_eq!(
+            res.messages[1].msg,
+            cosmwasm_std::CosmosMsg::Bank(BankMsg::Send {
+                to_address: burn.to_string(),
+                amount: coins(50, PAYMENT_DENOM),
+            })
+        );
+        let global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+        assert_eq!(
+            global_state.global_reward_index,
+            Decimal256::from_atomics(10u128, 0).unwrap()
+        );
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::AccruedFees {}).unwrap();
+        let fees: AccruedFeesResponse = from_json(&res).unwrap();
+        assert_eq!(fees.treasury_balance, Uint128::new(100));
+        assert_eq!(fees.total_accrued, Uint128::new(100));
+        assert_eq!(fees.total_burned, Uint128::new(50));
+
+        // Only the admin withdraws, and never more than the balance
+        let withdraw =
+            |amount: Option<u128>, recipient: Option<&Addr>| ExecuteMsg::WithdrawTreasury {
+                amount: amount.map(Uint128::new),
+                recipient: recipient.map(|r| r.to_string()),
+            };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&user, &[]),
+            withdraw(None, None),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&creator, &[]),
+            withdraw(Some(101), None),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds {}));
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&creator, &[]),
+            withdraw(Some(40), Some(&user)),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            cosmwasm_std::CosmosMsg::Bank(BankMsg::Send {
+                to_address: user.to_string(),
+                amount: coins(40, PAYMENT_DENOM),
+            })
+        );
+
+        // The rest goes to the admin by default
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&creator, &[]),
+            withdraw(None, None),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            cosmwasm_std::CosmosMsg::Bank(BankMsg::Send {
+                to_address: creator.to_string(),
+                amount: coins(60, PAYMENT_DENOM),
+            })
+        );
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::AccruedFees {}).unwrap();
+        let fees: AccruedFeesResponse = from_json(&res).unwrap();
+        assert_eq!(fees.treasury_balance, Uint128::zero());
+        assert_eq!(fees.total_withdrawn, Uint128::new(100));
+    }
+
+    #[test]
+    fn test_update_fee_split() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let user = deps.api.addr_make("user");
+        let burn = deps.api.addr_make("burn");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+
+        setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+
+        let fee_split = FeeSplit {
+            treasury_bps: 1_000,
+            burn_bps: 500,
+            reward_bps: 8_500,
+        };
+        let update = |burn_addr: Option<&Addr>| ExecuteMsg::UpdateFeeSplit {
+            fee_split: fee_split.clone(),
+            burn_addr: burn_addr.map(|addr| addr.to_string()),
+        };
+
+        let info = message_info(&user, &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, update(Some(&burn))).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // Burning needs somewhere to send the tokens
+        let info = message_info(&creator, &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, update(None)).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidFeeSplit { .. }));
+
+        let broken = ExecuteMsg::UpdateFeeSplit {
+            fee_split: FeeSplit {
+                reward_bps: 9_000,
+                ..fee_split.clone()
+            },
+            burn_addr: Some(burn.to_string()),
+        };
+        let info = message_info(&creator, &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, broken).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidFeeSplit { .. }));
+
+        let info = message_info(&creator, &[]);
+        execute(deps.as_mut(), mock_env(), info, update(Some(&burn))).unwrap();
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        assert_eq!(config.fee_split, fee_split);
+        assert_eq!(config.burn_addr, Some(burn));
+
+        // The burn address is kept when not given
+        let info = message_info(&creator, &[]);
+        execute(deps.as_mut(), mock_env(), info, update(None)).unwrap();
+    }
+
+    #[test]
+    fn test_emissions_accrue_between_calls() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let user = deps.api.addr_make("user");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+
+        setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+        mock_querier_with_nft(
+            &mut deps.querier,
+            &cw721,
+            "1",
+            &user,
+            TraitExtension::default(),
+        );
+
+        TOKEN_INFO
+            .save(
+                deps.as_mut().storage,
+                "1",
+                &TokenInfo {
+                    current_shares: Uint128::new(100),
+                    reward_debt: Uint128::zero(),
+                    pending_rewards: Uint128::zero(),
+                    ascension_attempts: 0,
+                },
+            )
+            .unwrap();
+        let mut global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+        global_state.total_shares = Uint128::new(100);
+        GLOBAL_STATE
+            .save(deps.as_mut().storage, &global_state)
+            .unwrap();
+
+        // Only the admin sets the schedule
+        let schedule = ExecuteMsg::UpdateEmissionSchedule {
+            rate_per_second: Uint128::new(10),
+            halving_interval: None,
+        };
+        let info = message_info(&user, &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, schedule.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let info = message_info(&creator, &[]);
+        execute(deps.as_mut(), mock_env(), info, schedule).unwrap();
+
+        // Funding needs the payment denom; anything else comes back
+        let info = message_info(&user, &coins(5, "uatom"));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::FundEmissions {},
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidPayment {}));
+
+        let funds = vec![
+            Coin::new(1000u128, PAYMENT_DENOM),
+            Coin::new(5u128, "uatom"),
+        ];
+        let info = message_info(&user, &funds);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::FundEmissions {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            cosmwasm_std::CosmosMsg::Bank(BankMsg::Send {
+                to_address: user.to_string(),
+                amount: coins(5, "uatom"),
+            })
+        );
+
+        // 30 seconds later the query projects what the next call will accrue
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(30);
+        let msg = QueryMsg::GetPendingRewards {
+            token_id: "1".to_string(),
+        };
+        let res: PendingRewardsResponse =
+            from_json(query(deps.as_ref(), later.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.accumulated_rewards, Uint128::new(300));
+        assert_eq!(res.projected_emissions, Uint128::new(300));
+
+        // Harvesting accrues them first and pays them out
+        let msg = ExecuteMsg::Harvest {
+            token_id: "1".to_string(),
+            recipient: None,
+            msg: None,
+        };
+        let res = execute(deps.as_mut(), later.clone(), message_info(&user, &[]), msg).unwrap();
+        let payout = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "final_payout")
+            .unwrap();
+        assert_eq!(payout.value, "300");
+
+        let res: EmissionScheduleResponse =
+            from_json(query(deps.as_ref(), later, QueryMsg::EmissionSchedule {}).unwrap()).unwrap();
+        assert_eq!(res.current_rate, Uint128::new(10));
+        assert_eq!(res.reserve, Uint128::new(700));
+        assert_eq!(res.total_emitted, Uint128::new(300));
+    }
+
+    #[test]
+    fn test_reward_index_carries_dust() {
+        let mut global_state = GlobalState {
+            total_shares: Uint128::zero(),
+            global_reward_index: Decimal256::zero(),
+            spin_nonce: 0,
+            undistributed_dust: Uint128::zero(),
+        };
+
+        // Nothing staked: kept for later instead of stranded
+        distribute_rewards(&mut global_state, Uint128::new(5)).unwrap();
+        assert_eq!(global_state.undistributed_dust, Uint128::new(5));
+
+        // 5 over 1e20 shares is below the index precision; it is carried, not lost
+        global_state.total_shares = Uint128::new(100_000_000_000_000_000_000);
+        distribute_rewards(&mut global_state, Uint128::zero()).unwrap();
+        assert!(global_state.global_reward_index.is_zero());
+        assert_eq!(global_state.undistributed_dust, Uint128::new(5));
+
+        distribute_rewards(&mut global_state, Uint128::new(995)).unwrap();
+        assert_eq!(
+            global_state.global_reward_index,
+            Decimal256::from_str("0.00000000000000001").unwrap()
+        );
+        assert!(global_state.undistributed_dust.is_zero());
+
+        // Small spins over a few shares now move the index instead of adding 0
+        global_state.total_shares = Uint128::new(3);
+        global_state.global_reward_index = Decimal256::zero();
+        distribute_rewards(&mut global_state, Uint128::new(2)).unwrap();
+        distribute_rewards(&mut global_state, Uint128::new(2)).unwrap();
+        let holder = TokenInfo {
+            current_shares: Uint128::new(1),
+            reward_debt: Uint128::zero(),
+            pending_rewards: Uint128::zero(),
+            ascension_attempts: 0,
+        };
+        assert_eq!(
+            accrued_rewards(&holder, global_state.global_reward_index).unwrap(),
+            Uint128::new(1)
+        );
+        let whale = TokenInfo {
+            current_shares: Uint128::new(2),
+            ..holder
+        };
+        assert_eq!(
+            accrued_rewards(&whale, global_state.global_reward_index).unwrap(),
+            Uint128::new(2)
+        );
+    }
+
+    #[test]
+    fn test_migrate_first_release_state() {
+        let mut deps = mock_deps_custom();
+        let admin = deps.api.addr_make("admin");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+
+        // State as the first release wrote it
+        let legacy_config = LegacyGameConfig {
+            payment_denom: PAYMENT_DENOM.to_string(),
+            spin_cost: Uint128::new(1_000_000),
+            mint_cost: Uint128::new(100),
+            mint_cost_increment: Uint128::new(10),
+            cw721_addr: cw721.clone(),
+            oracle_addr: oracle,
+        };
+        LEGACY_CONFIG
+            .save(deps.as_mut().storage, &legacy_config)
+            .unwrap();
+        deps.storage.set(
+            b"global_state",
+            br#"{"total_shares":"150","global_reward_index":"7","spin_nonce":3}"#,
+        );
+        let legacy_token = TokenInfo {
+            current_shares: Uint128::new(100),
+            reward_debt: Uint128::new(500),
+            pending_rewards: Uint128::new(20),
+            ascension_attempts: 0,
+        };
+        TOKEN_INFO
+            .save(deps.as_mut().storage, "1", &legacy_token)
+            .unwrap();
+        // 100 * 7 - 500 accrued plus 20 already pending
+        let pending_before = Uint128::new(220);
+        BIOMASS
+            .save(
+                deps.as_mut().storage,
+                &GlobalBiomass {
+                    total_base_cap: 30,
+                    total_base_stem: 30,
+                    total_base_spores: 30,
+                },
+            )
+            .unwrap();
+        let stats = GameStats {
+            total_minted: 3,
+            ..Default::default()
+        };
+        GAME_STATS.save(deps.as_mut().storage, &stats).unwrap();
+
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap_err();
+        assert!(matches!(err, ContractError::Std(_)));
+
+        let msg = MigrateMsg {
+            admin: Some(admin.to_string()),
+        };
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        assert_eq!(config.admin, admin);
+        assert_eq!(config.payment, Denom::Native(PAYMENT_DENOM.to_string()));
+        assert_eq!(config.mint_pricing.price(2).unwrap(), Uint128::new(120));
+
+        let global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+        assert_eq!(
+            global_state.global_reward_index,
+            Decimal256::from_atomics(7u128, 0).unwrap()
+        );
+        assert_eq!(global_state.spin_nonce, 3);
+
+        let token = TOKEN_INFO.load(deps.as_ref().storage, "1").unwrap();
+        let accrued = accrued_rewards(&token, global_state.global_reward_index).unwrap();
+        assert_eq!(token.pending_rewards + accrued, pending_before);
+
+        assert!(TREASURY.exists(deps.as_ref().storage));
+        assert!(EMISSIONS.exists(deps.as_ref().storage));
+
+        // The first Sun Line is taken right away: 90 score over 3 mushrooms
+        let epoch = CANOPY_EPOCH.load(deps.as_ref().storage).unwrap();
+        assert_eq!(epoch.sun_line, Decimal::from_ratio(30u128, 1u128));
+    }
+
+    #[test]
+    fn test_shadow_zone_uses_epoch_sun_line() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let user = deps.api.addr_make("user");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+
+        setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+
+        // Three mushrooms in a balanced ecosystem: the average score is 100
+        let biomass = GlobalBiomass {
+            total_base_cap: 100,
+            total_base_stem: 100,
+            total_base_spores: 100,
+        };
+        BIOMASS.save(deps.as_mut().storage, &biomass).unwrap();
+        let stats = GameStats {
+            total_minted: 3,
+            ..Default::default()
+        };
+        GAME_STATS.save(deps.as_mut().storage, &stats).unwrap();
+
+        // A weak mushroom scoring 30
+        let weak = TraitExtension {
+            base_cap: 10,
+            base_stem: 10,
+            base_spores: 10,
+            ..TraitExtension::default()
+        };
+        mock_querier_with_nft(&mut deps.querier, &cw721, "1", &user, weak.clone());
+        TOKEN_INFO
+            .save(
+                deps.as_mut().storage,
+                "1",
+                &TokenInfo {
+                    current_shares: Uint128::new(100),
+                    reward_debt: Uint128::zero(),
+                    pending_rewards: Uint128::new(1000),
+                    ascension_attempts: 0,
+                },
+            )
+            .unwrap();
+        let mut global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+        global_state.total_shares = Uint128::new(100);
+        GLOBAL_STATE
+            .save(deps.as_mut().storage, &global_state)
+            .unwrap();
+
+        let pending_query = QueryMsg::GetPendingRewards {
+            token_id: "1".to_string(),
+        };
+
+        // No Sun Line until the first epoch boundary
+        let res: PendingRewardsResponse =
+            from_json(query(deps.as_ref(), mock_env(), pending_query.clone()).unwrap()).unwrap();
+        assert_eq!(res.canopy_multiplier, Decimal::one());
+
+        // The next epoch snapshots the average
+        let mut next_epoch = mock_env();
+        next_epoch.block.time = next_epoch.block.time.plus_seconds(DEFAULT_EPOCH_LENGTH);
+        let res: SunLineResponse =
+            from_json(query(deps.as_ref(), next_epoch.clone(), QueryMsg::SunLine {}).unwrap())
+                .unwrap();
+        assert_eq!(res.epoch, 1);
+        assert_eq!(res.sun_line, Decimal::from_ratio(100u128, 1u128));
+        assert_eq!(res.shadow_line, Decimal::from_ratio(80u128, 1u128));
+
+        let res: PendingRewardsResponse =
+            from_json(query(deps.as_ref(), next_epoch.clone(), pending_query).unwrap()).unwrap();
+        assert_eq!(res.canopy_multiplier, Decimal::zero());
+        assert_eq!(res.estimated_payout, Uint128::zero());
+
+        let msg = ExecuteMsg::Harvest {
+            token_id: "1".to_string(),
+            recipient: None,
+            msg: None,
+        };
+        let res = execute(deps.as_mut(), next_epoch, message_info(&user, &[]), msg).unwrap();
+        let payout = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "final_payout")
+            .unwrap();
+        assert_eq!(payout.value, "0");
+        assert_eq!(CANOPY_EPOCH.load(deps.as_ref().storage).unwrap().epoch, 1);
+
+        // A mushroom at the average stays in the sun
+        let average = TraitExtension {
+            base_cap: 40,
+            base_stem: 30,
+            base_spores: 30,
+            ..TraitExtension::default()
+        };
+        let sun_line = Decimal::from_ratio(100u128, 1u128);
+        assert_eq!(
+            calculate_canopy_multiplier(&GameRules::default(), &biomass, &average, sun_line),
+            Decimal::one()
+        );
+        assert_eq!(
+            calculate_canopy_multiplier(&GameRules::default(), &biomass, &weak, sun_line),
+            Decimal::zero()
+        );
+    }
+
+    #[test]
+    fn test_ecosystem_history_snapshots_and_prunes() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+
+        setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+
+        let mut config = CONFIG.load(deps.as_ref().storage).unwrap();
+        config.history_retention = 3;
+        CONFIG.save(deps.as_mut().storage, &config).unwrap();
+
+        let biomass = GlobalBiomass {
+            total_base_cap: 200,
+            total_base_stem: 100,
+            total_base_spores: 100,
+        };
+        BIOMASS.save(deps.as_mut().storage, &biomass).unwrap();
+        let stats = GameStats {
+            total_minted: 4,
+            total_mint_volume: Uint128::new(4_000_000),
+            ..Default::default()
+        };
+        GAME_STATS.save(deps.as_mut().storage, &stats).unwrap();
+
+        // Epochs 1, 2, 4 and 5; nothing happens during epoch 3
+        for epoch in [1u64, 2, 4, 5] {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(epoch * DEFAULT_EPOCH_LENGTH);
+            advance_canopy_epoch(deps.as_mut().storage, &env).unwrap();
+        }
+
+        let history = |start_after: Option<u64>, limit: Option<u32>| -> Vec<u64> {
+            let res: EcosystemHistoryResponse = from_json(
+                query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::EcosystemHistory { start_after, limit },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            res.snapshots.iter().map(|s| s.epoch).collect()
+        };
+
+        // Epochs 0 to 2 fell out of the three epoch window
+        assert_eq!(history(None, None), vec![4, 5]);
+        assert_eq!(history(Some(4), None), vec![5]);
+        assert_eq!(history(None, Some(1)), vec![4]);
+
+        let snapshot = ECOSYSTEM_HISTORY.load(deps.as_ref().storage, 5).unwrap();
+        assert_eq!(
+            snapshot.time,
+            mock_env().block.time.plus_seconds(5 * DEFAULT_EPOCH_LENGTH)
+        );
+        assert_eq!(snapshot.biomass, biomass);
+        assert_eq!(snapshot.cap_multiplier, Decimal::from_ratio(2u128, 3u128));
+        assert_eq!(
+            snapshot.stem_multiplier,
+            trait_multiplier(&config.rules, &biomass, &TraitTarget::Stem)
+        );
+        assert_eq!(
+            snapshot.sun_line,
+            CANOPY_EPOCH.load(deps.as_ref().storage).unwrap().sun_line
+        );
+        assert_eq!(snapshot.active_supply, 4);
+        assert_eq!(snapshot.total_mint_volume, Uint128::new(4_000_000));
+        assert_eq!(snapshot.mint_price, config.mint_pricing.price(4).unwrap());
+    }
+
+    #[test]
+    fn test_harvest_biomass_is_time_weighted() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+
+        setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+
+        let balanced = GlobalBiomass {
+            total_base_cap: 100,
+            total_base_stem: 100,
+            total_base_spores: 100,
+        };
+        BIOMASS.save(deps.as_mut().storage, &balanced).unwrap();
+
+        // An hour before the query, a whale floods the pool with caps
+        let whale = GlobalBiomass {
+            total_base_cap: 1_000,
+            ..balanced.clone()
+        };
+        let start = mock_env().block.time;
+        save_biomass(deps.as_mut().storage, start.plus_seconds(82_800), &whale).unwrap();
+
+        let mut env = mock_env();
+        env.block.time = start.plus_seconds(86_399);
+        let res: EcosystemMetricsResponse =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetEcosystemMetrics {}).unwrap())
+                .unwrap();
+        assert_eq!(res.total_biomass, whale);
+        // (100 * 82_800 + 1_000 * 3_599) / 86_399
+        assert_eq!(res.time_weighted_biomass.total_base_cap, 137);
+        assert_eq!(res.time_weighted_biomass.total_base_stem, 100);
+
+        // The rollover keeps the previous epoch in the window
+        env.block.time = start.plus_seconds(86_400 + 3_600);
+        advance_canopy_epoch(deps.as_mut().storage, &env).unwrap();
+        assert_eq!(
+            time_weighted_biomass(deps.as_ref().storage, env.block.time)
+                .unwrap()
+                .total_base_cap,
+            (100 * 82_800 + 1_000 * 7_200) / 90_000
+        );
+    }
+
+    #[test]
+    fn test_update_game_rules() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+        let owner = deps.api.addr_make("owner");
+
+        setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+
+        let rules = GameRules {
+            spin_cost_multipliers: vec![3, 3, 3, 5, 10, 20],
+            shadow_zone: Decimal::percent(50),
+            ..GameRules::default()
+        };
+        let update = ExecuteMsg::UpdateGameRules {
+            rules: rules.clone(),
+        };
+
+        // Admin only, and validated
+        let info = message_info(&owner, &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, update.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let broken = ExecuteMsg::UpdateGameRules {
+            rules: GameRules {
+                spin_cost_multipliers: vec![3],
+                ..rules.clone()
+            },
+        };
+        let info = message_info(&creator, &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, broken).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidGameRules { .. }));
+
+        let info = message_info(&creator, &[]);
+        execute(deps.as_mut(), mock_env(), info, update).unwrap();
+        let stored: GameRules =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::GameRules {}).unwrap()).unwrap();
+        assert_eq!(stored, rules);
+
+        // A substrate 0 spin now costs 3x the base price
+        mock_querier_with_nft(
+            &mut deps.querier,
+            &cw721,
+            "1",
+            &owner,
+            TraitExtension::default(),
+        );
+        let spin = ExecuteMsg::Spin {
+            token_id: "1".to_string(),
+            trait_target: TraitTarget::Cap,
+            pay_from_rewards: false,
+        };
+        let info = message_info(&owner, &coins(1_000_000, PAYMENT_DENOM));
+        let err = execute(deps.as_mut(), mock_env(), info, spin.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds {}));
+        let info = message_info(&owner, &coins(3_000_000, PAYMENT_DENOM));
+        execute(deps.as_mut(), mock_env(), info, spin).unwrap();
+
+        let res: SunLineResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::SunLine {}).unwrap()).unwrap();
+        assert_eq!(res.shadow_line, res.sun_line * Decimal::percent(50));
+    }
+
+    #[test]
+    fn test_recycle_settles_rewards_and_composts() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let user = deps.api.addr_make("user");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+
+        setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+
+        let mut config = CONFIG.load(deps.as_ref().storage).unwrap();
+        config.mint_pricing = PricingCurve::Linear {
+            base: Uint128::new(1_000_000),
+            increment: Uint128::zero(),
+        };
+        config.rules.compost_refund_bps = 5_000;
+        CONFIG.save(deps.as_mut().storage, &config).unwrap();
+        TREASURY
+            .save(
+                deps.as_mut().storage,
+                &Treasury {
+                    balance: Uint128::new(300_000),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        BIOMASS
+            .save(
+                deps.as_mut().storage,
+                &GlobalBiomass {
+                    total_base_cap: 100,
+                    total_base_stem: 100,
+                    total_base_spores: 100,
+                },
+            )
+            .unwrap();
+        let stats = GameStats {
+            total_minted: 2,
+            ..Default::default()
+        };
+        GAME_STATS.save(deps.as_mut().storage, &stats).unwrap();
+
+        // A weak mushroom deep in the Shadow Zone, next to another holding 100 shares
+        let weak = TraitExtension {
+            base_cap: 1,
+            base_stem: 1,
+            base_spores: 1,
+            ..TraitExtension::default()
+        };
+        mock_querier_with_nft(&mut deps.querier, &cw721, "1", &user, weak);
+        for token_id in ["1", "2"] {
+            TOKEN_INFO
+                .save(
+                    deps.as_mut().storage,
+                    token_id,
+                    &TokenInfo {
+                        current_shares: Uint128::new(100),
+                        reward_debt: Uint128::zero(),
+                        pending_rewards: Uint128::new(1_000),
+                        ascension_attempts: 0,
+                    },
+                )
+                .unwrap();
+        }
+        let mut global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+        global_state.total_shares = Uint128::new(200);
+        GLOBAL_STATE
+            .save(deps.as_mut().storage, &global_state)
+            .unwrap();
+        let mut epoch = CANOPY_EPOCH.load(deps.as_ref().storage).unwrap();
+        epoch.sun_line = Decimal::from_ratio(100u128, 1u128);
+        CANOPY_EPOCH.save(deps.as_mut().storage, &epoch).unwrap();
+
+        let msg = ExecuteMsg::Recycle {
+            token_id: "1".to_string(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), message_info(&user, &[]), msg).unwrap();
+
+        let attr = |key: &str| {
+            res.attributes
+                .iter()
+                .find(|a| a.key == key)
+                .unwrap()
+                .value
+                .clone()
+        };
+        assert_eq!(attr("final_payout"), "0");
+        assert_eq!(attr("recycled_amount"), "1000");
+        // Half of the 1M mint price, capped by the treasury
+        assert_eq!(attr("compost_refund"), "300000");
+
+        assert_eq!(res.messages.len(), 2);
+        match &res.messages[1].msg {
+            CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                assert_eq!(to_address, &user.to_string());
+                assert_eq!(amount, &coins(300_000, PAYMENT_DENOM));
+            }
+            _ => panic!("Expected Bank Send message"),
+        }
+
+        // The forfeit went to the remaining mushroom
+        let global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+        assert_eq!(global_state.total_shares, Uint128::new(100));
+        assert_eq!(
+            global_state.global_reward_index,
+            Decimal256::from_ratio(10u128, 1u128)
+        );
+        assert!(!TOKEN_INFO.has(deps.as_ref().storage, "1"));
+
+        let treasury = TREASURY.load(deps.as_ref().storage).unwrap();
+        assert_eq!(treasury.balance, Uint128::zero());
+        assert_eq!(treasury.total_composted, Uint128::new(300_000));
+    }
+
+    #[test]
+    fn test_failed_spin_keeps_value_with_three_primordial_genes() {
+        // (genes, value after a failed spin on a -2 Cap)
+        let cases = [
+            (vec![4, 4, 4, 0, 0, 0, 0, 0], "-2"),
+            // Spores genes used to be counted as Primordial
+            (vec![3, 3, 3, 0, 0, 0, 0, 0], "-3"),
+        ];
+        for (genes, expected) in cases {
+            let mut deps = mock_deps_custom();
+            let creator = deps.api.addr_make("creator");
+            let user = deps.api.addr_make("user");
+            let cw721 = deps.api.addr_make("cw721");
+            let oracle = deps.api.addr_make("oracle");
+
+            setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+
+            // Every spin fails
+            let mut config = CONFIG.load(deps.as_ref().storage).unwrap();
+            config.rules.spin_success_thresholds = vec![255; crate::rules::SUBSTRATE_LEVELS];
+            CONFIG.save(deps.as_mut().storage, &config).unwrap();
+
+            mock_querier_with_nft(
+                &mut deps.querier,
+                &cw721,
+                "1",
+                &user,
+                TraitExtension {
+                    cap: -2,
+                    genes,
+                    ..TraitExtension::default()
+                },
+            );
+            TOKEN_INFO
+                .save(
+                    deps.as_mut().storage,
+                    "1",
+                    &TokenInfo {
+                        current_shares: Uint128::zero(),
+                        reward_debt: Uint128::zero(),
+                        pending_rewards: Uint128::zero(),
+                        ascension_attempts: 0,
+                    },
+                )
+                .unwrap();
+
+            let spin = ExecuteMsg::Spin {
+                token_id: "1".to_string(),
+                trait_target: TraitTarget::Cap,
+                pay_from_rewards: false,
+            };
+            let info = message_info(&user, &coins(1_000_000, PAYMENT_DENOM));
+            execute(deps.as_mut(), mock_env(), info, spin).unwrap();
+
+            let resolve = ExecuteMsg::ResolveSpin {
+                token_id: "1".to_string(),
+            };
+            let res =
+                execute(deps.as_mut(), mock_env(), message_info(&user, &[]), resolve).unwrap();
+            let attr = |key: &str| {
+                res.attributes
+                    .iter()
+                    .find(|a| a.key == key)
+                    .unwrap()
+                    .value
+                    .clone()
+            };
+            assert_eq!(attr("success"), "false");
+            assert_eq!(attr("new_value"), expected);
+        }
+    }
+
+    #[test]
+    fn test_recycle_rejected_during_pending_spin() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let user = deps.api.addr_make("user");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+
+        setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+        mock_querier_with_nft(
+            &mut deps.querier,
+            &cw721,
+            "1",
+            &user,
+            TraitExtension::default(),
+        );
+        TOKEN_INFO
+            .save(
+                deps.as_mut().storage,
+                "1",
+                &TokenInfo {
+                    current_shares: Uint128::zero(),
+                    reward_debt: Uint128::zero(),
+                    pending_rewards: Uint128::zero(),
+                    ascension_attempts: 0,
+                },
+            )
+            .unwrap();
+
+        let spin = ExecuteMsg::Spin {
+            token_id: "1".to_string(),
+            trait_target: TraitTarget::Cap,
+            pay_from_rewards: false,
+        };
+        let info = message_info(&user, &coins(1_000_000, PAYMENT_DENOM));
+        execute(deps.as_mut(), mock_env(), info, spin).unwrap();
+
+        let recycle = ExecuteMsg::Recycle {
+            token_id: "1".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&user, &[]),
+            recycle.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::HasPendingSpin {}));
+        assert!(TOKEN_INFO.has(deps.as_ref().storage, "1"));
+
+        // Once the spin resolves the mushroom can be recycled
+        let resolve = ExecuteMsg::ResolveSpin {
+            token_id: "1".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&user, &[]), resolve).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), message_info(&user, &[]), recycle).unwrap();
+        assert_eq!(res.attributes[0].value, "recycle");
+        assert!(!TOKEN_INFO.has(deps.as_ref().storage, "1"));
+    }
+
+    #[test]
+    fn test_spin_paid_from_rewards() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+        let owner = deps.api.addr_make("owner");
+
+        setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+        mock_querier_with_nft(
+            &mut deps.querier,
+            &cw721,
+            "1",
+            &owner,
+            TraitExtension::default(),
+        );
+
+        // 400k pending plus 200k accrued through the index
+        TOKEN_INFO
+            .save(
+                deps.as_mut().storage,
+                "1",
+                &TokenInfo {
+                    current_shares: Uint128::new(100),
+                    reward_debt: Uint128::zero(),
+                    pending_rewards: Uint128::new(400_000),
+                    ascension_attempts: 0,
+                },
+            )
+            .unwrap();
+        let mut global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+        global_state.total_shares = Uint128::new(100);
+        global_state.global_reward_index = Decimal256::from_ratio(2_000u128, 1u128);
+        GLOBAL_STATE
+            .save(deps.as_mut().storage, &global_state)
+            .unwrap();
+
+        let msg = ExecuteMsg::Spin {
+            token_id: "1".to_string(),
+            trait_target: TraitTarget::Cap,
+            pay_from_rewards: true,
+        };
+
+        // 600k doesn't cover the 1M spin
+        let info = message_info(&owner, &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds {}));
+
+        global_state.global_reward_index = Decimal256::from_ratio(10_000u128, 1u128);
+        GLOBAL_STATE
+            .save(deps.as_mut().storage, &global_state)
+            .unwrap();
+
+        // Coins sent along are returned
+        let info = message_info(&owner, &coins(5, PAYMENT_DENOM));
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        let token_info = TOKEN_INFO.load(deps.as_ref().storage, "1").unwrap();
+        assert_eq!(token_info.pending_rewards, Uint128::new(400_000));
+        assert_eq!(token_info.reward_debt, Uint128::new(1_000_000));
+        let pending = PENDING_SPINS.load(deps.as_ref().storage, "1").unwrap();
+        assert_eq!(pending.bid_amount, Uint128::new(1_000_000));
+    }
+
+    #[test]
+    fn test_harvest_to_recipient() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let user = deps.api.addr_make("user");
+        let vault = deps.api.addr_make("vault");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+
+        setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+        mock_querier_with_nft(
+            &mut deps.querier,
+            &cw721,
+            "1",
+            &user,
+            TraitExtension::default(),
+        );
+
+        let fill = |deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>| {
+            let mut global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+            global_state.total_shares = Uint128::new(100);
+            GLOBAL_STATE
+                .save(deps.as_mut().storage, &global_state)
+                .unwrap();
+            TOKEN_INFO
+                .save(
+                    deps.as_mut().storage,
+                    "1",
+                    &TokenInfo {
+                        current_shares: Uint128::new(100),
+                        reward_debt: Uint128::zero(),
+                        pending_rewards: Uint128::new(1000),
+                        ascension_attempts: 0,
+                    },
+                )
+                .unwrap();
+        };
+
+        // Plain transfer to another address
+        fill(&mut deps);
+        let msg = ExecuteMsg::Harvest {
+            token_id: "1".to_string(),
+            recipient: Some(vault.to_string()),
+            msg: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), message_info(&user, &[]), msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: vault.to_string(),
+                amount: coins(1000, PAYMENT_DENOM),
+            })
+        );
+
+        // A contract call with the payout attached
+        fill(&mut deps);
+        let deposit = to_json_binary(&"deposit").unwrap();
+        let msg = ExecuteMsg::Harvest {
+            token_id: "1".to_string(),
+            recipient: Some(vault.to_string()),
+            msg: Some(deposit.clone()),
+        };
+        let res = execute(deps.as_mut(), mock_env(), message_info(&user, &[]), msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: vault.to_string(),
+                msg: deposit,
+                funds: coins(1000, PAYMENT_DENOM),
+            })
+        );
+
+        // Only the owner harvests, wherever the payout goes
+        let msg = ExecuteMsg::Harvest {
+            token_id: "1".to_string(),
+            recipient: Some(vault.to_string()),
+            msg: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), message_info(&vault, &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+    }
+
+    #[test]
+    fn test_ascend_pity_counter() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let user = deps.api.addr_make("user");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+
+        setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+
+        // The first attempt can't succeed, the second always does
+        let mut config = CONFIG.load(deps.as_ref().storage).unwrap();
+        config.rules.ascend_chance_bps = 0;
+        config.rules.ascend_pity_bps = 0;
+        config.rules.ascend_guaranteed_attempt = 2;
+        CONFIG.save(deps.as_mut().storage, &config).unwrap();
+
+        let traits = TraitExtension {
+            cap: 3,
+            stem: 3,
+            spores: 3,
+            ..TraitExtension::default()
+        };
+        mock_querier_with_nft(&mut deps.querier, &cw721, "1", &user, traits);
+        let mut global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+        global_state.total_shares = Uint128::new(100);
+        GLOBAL_STATE
+            .save(deps.as_mut().storage, &global_state)
+            .unwrap();
+
+        let attempt = |deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>| {
+            TOKEN_INFO
+                .update(deps.as_mut().storage, "1", |info| -> StdResult<_> {
+                    let info = info.unwrap_or(TokenInfo {
+                        current_shares: Uint128::new(100),
+                        reward_debt: Uint128::zero(),
+                        pending_rewards: Uint128::zero(),
+                        ascension_attempts: 0,
+                    });
+                    Ok(TokenInfo {
+                        pending_rewards: Uint128::new(500),
+                        ..info
+                    })
+                })
+                .unwrap();
+            let request = ExecuteMsg::RequestAscend {
+                token_id: "1".to_string(),
+            };
+            execute(deps.as_mut(), mock_env(), message_info(&user, &[]), request).unwrap();
+            let resolve = ExecuteMsg::ResolveAscend {
+                token_id: "1".to_string(),
+            };
+            let res =
+                execute(deps.as_mut(), mock_env(), message_info(&user, &[]), resolve).unwrap();
+            let attr = |key: &str| {
+                res.attributes
+                    .iter()
+                    .find(|a| a.key == key)
+                    .unwrap()
+                    .value
+                    .clone()
+            };
+            (attr("success"), attr("ascension_attempts"))
+        };
+
+        assert_eq!(attempt(&mut deps), ("false".to_string(), "1".to_string()));
+        let res: PendingAscendResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetPendingAscend {
+                    token_id: "1".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.ascension_attempts, 1);
+        assert_eq!(res.success_chance_bps, 10_000);
+
+        assert_eq!(attempt(&mut deps), ("true".to_string(), "0".to_string()));
+        let token_info = TOKEN_INFO.load(deps.as_ref().storage, "1").unwrap();
+        assert_eq!(token_info.ascension_attempts, 0);
+    }
+
+    #[test]
+    fn test_ascend_failure_handling() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let user = deps.api.addr_make("user");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+
+        setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+
+        // Always fails, recycles 40% and keeps the stats
+        let mut config = CONFIG.load(deps.as_ref().storage).unwrap();
+        config.rules.ascend_chance_bps = 0;
+        config.rules.ascend_pity_bps = 0;
+        config.rules.ascend_guaranteed_attempt = 0;
+        config.rules.ascend_failure_burn_bps = 4_000;
+        config.rules.ascend_failure_resets_stats = false;
+        CONFIG.save(deps.as_mut().storage, &config).unwrap();
+
+        let traits = TraitExtension {
+            cap: 3,
+            stem: 3,
+            spores: 3,
+            genes: vec![0; 8],
+            ..TraitExtension::default()
+        };
+        mock_querier_with_nft(&mut deps.querier, &cw721, "1", &user, traits.clone());
+        let shares = calculate_shares(&config.rules, &traits);
+        TOKEN_INFO
+            .save(
+                deps.as_mut().storage,
+                "1",
+                &TokenInfo {
+                    current_shares: shares,
+                    reward_debt: Uint128::zero(),
+                    pending_rewards: Uint128::new(500),
+                    ascension_attempts: 0,
+                },
+            )
+            .unwrap();
+        let mut global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+        global_state.total_shares = shares + Uint128::new(100);
+        GLOBAL_STATE
+            .save(deps.as_mut().storage, &global_state)
+            .unwrap();
+
+        let simulation: AscendSimulationResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::SimulateAscend {
+                    token_id: "1".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(simulation.cost, Uint128::new(500));
+        assert_eq!(simulation.failure_cost, Uint128::new(200));
+        // Failing changes nothing about the shares
+        assert_eq!(
+            simulation.estimate.expected_shares,
+            Decimal::from_ratio(shares, 1u128)
+        );
+
+        let request = ExecuteMsg::RequestAscend {
+            token_id: "1".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&user, &[]), request).unwrap();
+        let resolve = ExecuteMsg::ResolveAscend {
+            token_id: "1".to_string(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), message_info(&user, &[]), resolve).unwrap();
+        assert_eq!(res.attributes[2].value, "false");
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "recycled_amount" && attr.value == "200"));
+
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => {
+                let update: spore_fates::cw721::ExecuteMsg = from_json(msg).unwrap();
+                match update {
+                    spore_fates::cw721::ExecuteMsg::UpdateTraits { traits: kept, .. } => {
+                        assert_eq!(kept, traits)
+                    }
+                    _ => panic!("Expected UpdateTraits"),
+                }
+            }
+            _ => panic!("Expected Wasm Execute"),
+        }
+
+        let token_info = TOKEN_INFO.load(deps.as_ref().storage, "1").unwrap();
+        assert_eq!(token_info.pending_rewards, Uint128::new(300));
+        assert_eq!(token_info.current_shares, shares);
+        assert_eq!(token_info.ascension_attempts, 1);
+        let stats = GAME_STATS.load(deps.as_ref().storage).unwrap();
+        assert_eq!(stats.total_rewards_recycled, Uint128::new(200));
+    }
+
+    #[test]
+    fn test_ascend_keeps_rewards_distributed_while_pending() {
+        for success in [false, true] {
+            let mut deps = mock_deps_custom();
+            let creator = deps.api.addr_make("creator");
+            let user = deps.api.addr_make("user");
+            let cw721 = deps.api.addr_make("cw721");
+            let oracle = deps.api.addr_make("oracle");
+
+            setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+
+            let mut config = CONFIG.load(deps.as_ref().storage).unwrap();
+            config.rules.ascend_chance_bps = if success { 10_000 } else { 0 };
+            config.rules.ascend_pity_bps = 0;
+            config.rules.ascend_guaranteed_attempt = 0;
+            config.rules.ascend_failure_burn_bps = 4_000;
+            CONFIG.save(deps.as_mut().storage, &config).unwrap();
+
+            let traits = TraitExtension {
+                cap: 3,
+                stem: 3,
+                spores: 3,
+                genes: vec![0; 8],
+                ..TraitExtension::default()
+            };
+            mock_querier_with_nft(&mut deps.querier, &cw721, "1", &user, traits.clone());
+            let shares = calculate_shares(&config.rules, &traits);
+            TOKEN_INFO
+                .save(
+                    deps.as_mut().storage,
+                    "1",
+                    &TokenInfo {
+                        current_shares: shares,
+                        reward_debt: Uint128::zero(),
+                        pending_rewards: Uint128::new(500),
+                        ascension_attempts: 0,
+                    },
+                )
+                .unwrap();
+            let mut global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+            global_state.total_shares = shares + Uint128::new(100);
+            GLOBAL_STATE
+                .save(deps.as_mut().storage, &global_state)
+                .unwrap();
+
+            let request = ExecuteMsg::RequestAscend {
+                token_id: "1".to_string(),
+            };
+            execute(deps.as_mut(), mock_env(), message_info(&user, &[]), request).unwrap();
+
+            // 10 per share lands while the ascend waits for its beacon
+            let mut global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+            let distributed = global_state.total_shares * Uint128::new(10);
+            distribute_rewards(&mut global_state, distributed).unwrap();
+            GLOBAL_STATE
+                .save(deps.as_mut().storage, &global_state)
+                .unwrap();
+
+            let resolve = ExecuteMsg::ResolveAscend {
+                token_id: "1".to_string(),
+            };
+            let res =
+                execute(deps.as_mut(), mock_env(), message_info(&user, &[]), resolve).unwrap();
+            assert_eq!(res.attributes[2].value, success.to_string());
+
+            let kept = if success { 0 } else { 300 };
+            let token_info = TOKEN_INFO.load(deps.as_ref().storage, "1").unwrap();
+            assert_eq!(
+                token_info.pending_rewards,
+                Uint128::new(kept) + shares * Uint128::new(10)
+            );
+            // Nothing earned before the resolve is counted twice
+            let global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+            assert_eq!(
+                accrued_rewards(&token_info, global_state.global_reward_index).unwrap(),
+                Uint128::zero()
+            );
+        }
+    }
+
+    #[test]
+    fn test_overmind_lifecycle() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let user = deps.api.addr_make("user");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+
+        setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+
+        let traits = TraitExtension {
+            cap: 3,
+            stem: 3,
+            spores: 3,
+            substrate: MAX_SUBSTRATE,
+            genes: vec![0; 8],
+            base_cap: 0,
+            base_stem: 0,
+            base_spores: 0,
+            generation: 0,
+        };
+        mock_querier_with_nft(&mut deps.querier, &cw721, "1", &user, traits.clone());
+
+        // 9 power squared, 8x for the Overmind
+        let rules = GameRules::default();
+        let shares = calculate_shares(&rules, &traits);
+        assert_eq!(shares, Uint128::new(81 * 8));
+
+        TOKEN_INFO
+            .save(
+                deps.as_mut().storage,
+                "1",
+                &TokenInfo {
+                    current_shares: shares,
+                    reward_debt: Uint128::zero(),
+                    pending_rewards: Uint128::new(1000),
+                    ascension_attempts: 0,
+                },
+            )
+            .unwrap();
+        let mut global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+        global_state.total_shares = shares;
+        GLOBAL_STATE
+            .save(deps.as_mut().storage, &global_state)
+            .unwrap();
+
+        // Ascension tops out below the Overmind
+        let msg = ExecuteMsg::RequestAscend {
+            token_id: "1".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), message_info(&user, &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::MaxSubstrate {}));
+
+        // A spin costs 20x
+        let msg = ExecuteMsg::Spin {
+            token_id: "1".to_string(),
+            trait_target: TraitTarget::Cap,
+            pay_from_rewards: false,
+        };
+        let info = message_info(&user, &coins(10_000_000, PAYMENT_DENOM));
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds {}));
+        let info = message_info(&user, &coins(20_000_000, PAYMENT_DENOM));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let resolve = ExecuteMsg::ResolveSpin {
+            token_id: "1".to_string(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), message_info(&user, &[]), resolve).unwrap();
+        assert_eq!(res.attributes[0].value, "resolve_spin");
+        assert_eq!(res.messages.len(), 1);
+
+        // Still harvests like any other mushroom
+        let msg = ExecuteMsg::Harvest {
+            token_id: "1".to_string(),
+            recipient: None,
+            msg: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), message_info(&user, &[]), msg).unwrap();
+        assert_eq!(res.attributes[0].value, "harvest");
+    }
+
+    #[test]
+    fn test_simulate_spin() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let user = deps.api.addr_make("user");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+
+        setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+        let schedule = ExecuteMsg::UpdateEmissionSchedule {
+            rate_per_second: Uint128::new(10),
+            halving_interval: None,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&creator, &[]),
+            schedule,
+        )
+        .unwrap();
+
+        let traits = TraitExtension {
+            cap: 1,
+            stem: 1,
+            spores: 1,
+            genes: vec![0; 8],
+            ..TraitExtension::default()
+        };
+        mock_querier_with_nft(&mut deps.querier, &cw721, "1", &user, traits);
+        TOKEN_INFO
+            .save(
+                deps.as_mut().storage,
+                "1",
+                &TokenInfo {
+                    current_shares: Uint128::new(9),
+                    reward_debt: Uint128::zero(),
+                    pending_rewards: Uint128::zero(),
+                    ascension_attempts: 0,
+                },
+            )
+            .unwrap();
+        let mut global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+        global_state.total_shares = Uint128::new(109);
+        GLOBAL_STATE
+            .save(deps.as_mut().storage, &global_state)
+            .unwrap();
+
+        let res: SpinSimulationResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::SimulateSpin {
+                    token_id: "1".to_string(),
+                    trait_target: TraitTarget::Cap,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        // A win takes +1 to +2, a loss drops it to -1
+        let half = Decimal::percent(50);
+        assert_eq!(res.current_value, 1);
+        assert_eq!(res.success_chance, half);
+        assert_eq!(
+            res.outcomes,
+            vec![
+                TraitValueOdds {
+                    value: -1,
+                    chance: half,
+                },
+                TraitValueOdds {
+                    value: 2,
+                    chance: half,
+                },
+            ]
+        );
+        assert_eq!(res.cost, Uint128::new(1_000_000));
+
+        // 9 shares now; 1 or 16 after the spin, against 100 held by others
+        let estimate = res.estimate;
+        assert_eq!(estimate.current_shares, Uint128::new(9));
+        assert_eq!(estimate.expected_shares, Decimal::from_str("8.5").unwrap());
+        assert_eq!(
+            estimate.expected_share_change,
+            SignedDecimal::from_str("-0.5").unwrap()
+        );
+        assert_eq!(
+            estimate.current_reward_rate,
+            Decimal::from_ratio(90u128, 109u128)
+        );
+        assert_eq!(
+            estimate.expected_reward_rate,
+            Decimal::from_ratio(10u128, 101u128) * half
+                + Decimal::from_ratio(160u128, 116u128) * half
+        );
+    }
+
+    #[test]
+    fn test_simulate_ascend() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let user = deps.api.addr_make("user");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+
+        setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+
+        let traits = TraitExtension {
+            cap: 3,
+            stem: 3,
+            spores: 3,
+            substrate: 1,
+            ..TraitExtension::default()
+        };
+        mock_querier_with_nft(&mut deps.querier, &cw721, "1", &user, traits.clone());
+        TOKEN_INFO
+            .save(
+                deps.as_mut().storage,
+                "1",
+                &TokenInfo {
+                    current_shares: Uint128::new(162),
+                    reward_debt: Uint128::zero(),
+                    pending_rewards: Uint128::new(500),
+                    ascension_attempts: 2,
+                },
+            )
+            .unwrap();
+
+        let simulate = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>| {
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::SimulateAscend {
+                    token_id: "1".to_string(),
+                },
+            )
+        };
+        let res: AscendSimulationResponse = from_json(simulate(&deps).unwrap()).unwrap();
+
+        // Two failures add 20% pity to the base 20%
+        assert_eq!(res.current_substrate, 1);
+        assert_eq!(res.success_chance, Decimal::percent(40));
+        assert_eq!(
+            res.outcomes,
+            vec![
+                SubstrateOdds {
+                    substrate: 1,
+                    chance: Decimal::percent(60),
+                },
+                SubstrateOdds {
+                    substrate: 2,
+                    chance: Decimal::percent(40),
+                },
+            ]
+        );
+        assert_eq!(res.cost, Uint128::new(500));
+        // Reset traits leave a power of 1: 2 shares at level 1, 3 at level 2
+        assert_eq!(
+            res.estimate.expected_shares,
+            Decimal::from_str("2.4").unwrap()
+        );
+
+        // Nothing to simulate past level 4
+        let top = TraitExtension {
+            substrate: 4,
+            ..traits
+        };
+        mock_querier_with_nft(&mut deps.querier, &cw721, "1", &user, top);
+        assert!(simulate(&deps).is_err());
+    }
+
+    #[test]
+    fn test_gene_edit() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let owner = deps.api.addr_make("owner");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+
+        setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+
+        let mut traits = TraitExtension {
+            genes: vec![4, 1, 1, 2, 0, 0, 0, 0],
+            ..TraitExtension::default()
+        };
+        traits.recalculate_base_stats();
+        mock_querier_with_nft(&mut deps.querier, &cw721, "1", &owner, traits.clone());
+        let shares = calculate_shares(&GameRules::default(), &traits);
+        TOKEN_INFO
+            .save(
+                deps.as_mut().storage,
+                "1",
+                &TokenInfo {
+                    current_shares: shares,
+                    reward_debt: Uint128::zero(),
+                    pending_rewards: Uint128::zero(),
+                    ascension_attempts: 0,
+                },
+            )
+            .unwrap();
+        let mut global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+        global_state.total_shares = shares;
+        GLOBAL_STATE
+            .save(deps.as_mut().storage, &global_state)
+            .unwrap();
+        let biomass = GlobalBiomass {
+            total_base_cap: traits.base_cap as u128,
+            total_base_stem: traits.base_stem as u128,
+            total_base_spores: traits.base_spores as u128,
+        };
+        BIOMASS.save(deps.as_mut().storage, &biomass).unwrap();
+
+        let request = |slot: u8| ExecuteMsg::RequestGeneEdit {
+            token_id: "1".to_string(),
+            slot,
+        };
+        let paid = message_info(&owner, &coins(1_000_000, PAYMENT_DENOM));
+
+        // Slot 8 doesn't exist, and replacing the Primordial costs 5x
+        let err = execute(deps.as_mut(), mock_env(), paid.clone(), request(8)).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidGeneSlot { slot: 8 }));
+        let err = execute(deps.as_mut(), mock_env(), paid.clone(), request(0)).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds {}));
+
+        // Rot is the cheapest to replace
+        let res = execute(deps.as_mut(), mock_env(), paid.clone(), request(5)).unwrap();
+        assert_eq!(res.attributes[0].value, "request_gene_edit");
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "cost" && attr.value == "1000000"));
+        let pending: PendingGeneEditResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetPendingGeneEdit {
+                    token_id: "1".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(pending.is_pending);
+        assert_eq!(pending.slot, 5);
+
+        // The token is locked until resolution
+        let spin = ExecuteMsg::Spin {
+            token_id: "1".to_string(),
+            trait_target: TraitTarget::Cap,
+            pay_from_rewards: false,
+        };
+        let err = execute(deps.as_mut(), mock_env(), paid, spin).unwrap_err();
+        assert!(matches!(err, ContractError::TokenLocked { .. }));
+
+        let resolve = ExecuteMsg::ResolveGeneEdit {
+            token_id: "1".to_string(),
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&creator, &[]),
+            resolve.clone(),
+        )
+        .unwrap();
+
+        let mut hasher = Sha256::new();
+        hasher.update([123u8; 32]);
+        hasher.update(b"1");
+        hasher.update(b"gene_edit");
+        let hash = hasher.finalize();
+        let new_gene = reroll_gene(GameRules::default().splice_mutation(0, 0), hash[0], hash[1]);
+        let mut edited = traits.clone();
+        edited.genes[5] = new_gene;
+        edited.recalculate_base_stats();
+
+        assert_eq!(res.attributes[0].value, "resolve_gene_edit");
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "new_gene" && attr.value == new_gene.to_string()));
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => {
+                let update: spore_fates::cw721::ExecuteMsg = from_json(msg).unwrap();
+                match update {
+                    spore_fates::cw721::ExecuteMsg::UpdateTraits { traits, .. } => {
+                        assert_eq!(traits, edited)
+                    }
+                    _ => panic!("Expected UpdateTraits"),
+                }
+            }
+            _ => panic!("Expected Wasm Execute"),
+        }
+
+        // Biomass and shares follow the new genes
+        let biomass = BIOMASS.load(deps.as_ref().storage).unwrap();
+        assert_eq!(biomass.total_base_cap, edited.base_cap as u128);
+        assert_eq!(biomass.total_base_stem, edited.base_stem as u128);
+        assert_eq!(biomass.total_base_spores, edited.base_spores as u128);
+        let new_shares = calculate_shares(&GameRules::default(), &edited);
+        let global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+        assert_eq!(global_state.total_shares, new_shares);
+        let token_info = TOKEN_INFO.load(deps.as_ref().storage, "1").unwrap();
+        assert_eq!(token_info.current_shares, new_shares);
+        // The fee went to the reward pool, with the editor's debt set before it
+        assert!(!global_state.global_reward_index.is_zero());
+        assert!(token_info.reward_debt.is_zero());
+
+        assert!(!LOCKED_TOKENS.has(deps.as_ref().storage, "1"));
+        let stats = GAME_STATS.load(deps.as_ref().storage).unwrap();
+        assert_eq!(stats.total_gene_edits, 1);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&creator, &[]),
+            resolve,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NoPendingGeneEdit {}));
+    }
+
+    #[test]
+    fn test_quote_mint() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+
+        let msg = InstantiateMsg {
+            payment: UncheckedDenom::Native(PAYMENT_DENOM.to_string()),
+            spin_cost: Uint128::new(100),
+            mint_pricing: PricingCurve::Sigmoid {
+                min: Uint128::new(100),
+                max: Uint128::new(50),
+                midpoint: 10,
+                width: 5,
+            },
+            oracle_addr: oracle.to_string(),
+            cw721_addr: cw721.to_string(),
+            min_round_delay: None,
+            max_block_time: None,
+            admin: None,
+            fee_split: None,
+            burn_addr: None,
+            epoch_length: None,
+            history_retention: None,
+            rules: None,
+        };
+        let info = message_info(&creator, &[]);
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidPricingCurve { .. }));
+
+        let msg = InstantiateMsg {
+            mint_pricing: PricingCurve::Exponential {
+                base: Uint128::new(1_000),
+                growth_bps: 500,
+            },
+            ..msg
+        };
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // 3 alive after one burn
+        let mut stats = GAME_STATS.load(deps.as_ref().storage).unwrap();
+        stats.total_minted = 4;
+        stats.total_burned = 1;
+        GAME_STATS.save(deps.as_mut().storage, &stats).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCurrentMintPrice {}).unwrap();
+        let price: MintPriceResponse = from_json(&res).unwrap();
+        assert_eq!(price.price, Uint128::new(1_157));
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::QuoteMint { quantity: 3 },
+        )
+        .unwrap();
+        let quote: MintQuoteResponse = from_json(&res).unwrap();
+        assert_eq!(
+            quote.prices,
+            vec![
+                Uint128::new(1_157),
+                Uint128::new(1_215),
+                Uint128::new(1_276)
+            ]
+        );
+        assert_eq!(quote.total_cost, Uint128::new(3_648));
+
+        assert!(query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::QuoteMint { quantity: 0 }
+        )
+        .is_err());
+        assert!(query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::QuoteMint {
+                quantity: MAX_QUOTE_QUANTITY + 1
+            }
+        )
+        .is_err());
     }
 
     #[test]
-    fn test_mint_bonding_curve() {
+    fn test_request_round_delay() {
         let mut deps = mock_deps_custom();
         let creator = deps.api.addr_make("creator");
         let user = deps.api.addr_make("user");
         let cw721 = deps.api.addr_make("cw721");
         let oracle = deps.api.addr_make("oracle");
 
-        // 1. Instantiate
         let msg = InstantiateMsg {
-            payment_denom: PAYMENT_DENOM.to_string(),
+            payment: UncheckedDenom::Native(PAYMENT_DENOM.to_string()),
             spin_cost: Uint128::new(100),
-            mint_cost: Uint128::new(100),
-            mint_cost_increment: Uint128::new(10),
+            mint_pricing: PricingCurve::Linear {
+                base: Uint128::new(100),
+                increment: Uint128::zero(),
+            },
             oracle_addr: oracle.to_string(),
             cw721_addr: cw721.to_string(),
+            min_round_delay: Some(0),
+            max_block_time: Some(6),
+            admin: None,
+            fee_split: None,
+            burn_addr: None,
+            epoch_length: None,
+            history_retention: None,
+            rules: None,
         };
         let info = message_info(&creator, &[]);
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidRoundDelay {}));
+
+        let msg = InstantiateMsg {
+            min_round_delay: Some(3),
+            ..msg
+        };
         instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        let env = mock_env_drand();
+        deps.querier.update_wasm(|query| match query {
+            CosmWasmQuery::Smart { msg, .. } => mock_oracle_query(msg),
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "skip".into(),
+            }),
+        });
 
-        // 2. Request Mint #1
+        // Two rounds may be published while the request waits for inclusion,
+        // and three more must follow before the target
         let info = message_info(&user, &coins(100, PAYMENT_DENOM));
-        let res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::RequestMint {}).unwrap();
-
-        assert_eq!(res.attributes[0].value, "request_mint");
-        assert_eq!(res.attributes[1].value, "1"); // mint_id
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::RequestMint {}).unwrap();
 
-        // Price hasn't increased yet (happens on resolve, not request)
-        let query_msg = QueryMsg::GetCurrentMintPrice {};
+        let query_msg = QueryMsg::GetPendingMint {
+            mint_id: "1".to_string(),
+        };
         let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
-        let price_response: MintPriceResponse = from_json(&res).unwrap();
-        assert_eq!(price_response.price, Uint128::new(100));
+        let pending: PendingMintResponse = from_json(&res).unwrap();
+        assert_eq!(pending.target_round, LATEST_ROUND + 2 + 3);
+    }
 
-        // 3. Request Mint #2 (same price since #1 hasn't resolved yet)
-        let info = message_info(&user, &coins(100, PAYMENT_DENOM));
-        let res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::RequestMint {}).unwrap();
-        assert_eq!(res.attributes[1].value, "2"); // mint_id
+    #[test]
+    fn test_resolve_rejects_published_round() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let user = deps.api.addr_make("user");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+
+        setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+        deps.querier.update_wasm(|query| match query {
+            CosmWasmQuery::Smart { msg, .. } => mock_oracle_query(msg),
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "skip".into(),
+            }),
+        });
+
+        // A request committing to a round that was already public
+        let pending = PendingMint {
+            player: user.clone(),
+            payment_amount: Uint128::zero(),
+            target_round: LATEST_ROUND,
+            mint_id: "1".to_string(),
+            requested_at: mock_env().block.time,
+        };
+        PENDING_MINTS
+            .save(deps.as_mut().storage, "1", &pending)
+            .unwrap();
+
+        let msg = ExecuteMsg::ResolveMint {
+            mint_id: "1".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), message_info(&user, &[]), msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::RoundAlreadyPublished {
+                round: LATEST_ROUND
+            }
+        ));
+
+        // Requests stored before `requested_at` existed still resolve
+        let legacy = format!(
+            r#"{{"player":"{user}","payment_amount":"0","target_round":{LATEST_ROUND},"mint_id":"1"}}"#
+        );
+        deps.storage.set(&PENDING_MINTS.key("1"), legacy.as_bytes());
+        assert_eq!(
+            PENDING_MINTS
+                .load(deps.as_ref().storage, "1")
+                .unwrap()
+                .requested_at,
+            Timestamp::default()
+        );
+        let msg = ExecuteMsg::ResolveMint {
+            mint_id: "1".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&user, &[]), msg).unwrap();
+        assert!(!PENDING_MINTS.has(deps.as_ref().storage, "1"));
     }
 
     #[test]
@@ -2010,9 +5383,7 @@ mod tests {
                         }),
                     }
                 } else {
-                    SystemResult::Err(SystemError::UnsupportedRequest {
-                        kind: "wrong contract".into(),
-                    })
+                    mock_oracle_query(msg)
                 }
             }
             _ => SystemResult::Err(SystemError::UnsupportedRequest {
@@ -2025,6 +5396,7 @@ mod tests {
             current_shares: Uint128::zero(),
             reward_debt: Uint128::zero(),
             pending_rewards: Uint128::zero(),
+            ascension_attempts: 0,
         };
         TOKEN_INFO
             .save(deps.as_mut().storage, "1", &dummy_info)
@@ -2032,8 +5404,25 @@ mod tests {
         TOKEN_INFO
             .save(deps.as_mut().storage, "2", &dummy_info)
             .unwrap();
-        // Set Mint Counter to 2 (since 1 and 2 exist)
-        MINT_COUNTER.save(deps.as_mut().storage, &2).unwrap();
+        // The counter holds the next id (1 and 2 exist)
+        MINT_COUNTER.save(deps.as_mut().storage, &3).unwrap();
+
+        // Parent 1 wins the even bytes left after 2 Primordial and 11 Rot
+        let preview: SplicePreviewResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PreviewSplice {
+                    parent_1_id: "1".to_string(),
+                    parent_2_id: "2".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(preview.slots[0].cap, Decimal::from_ratio(121u128, 256u128));
+        assert_eq!(preview.slots[0].stem, Decimal::from_ratio(122u128, 256u128));
+        assert_eq!(preview.substrate[0].chance, Decimal::one());
 
         // EXECUTE SPLICE
         let info = message_info(&user, &[]);
@@ -2041,11 +5430,89 @@ mod tests {
             parent_1_id: "1".to_string(),
             parent_2_id: "2".to_string(),
         };
-        let res = execute(deps.as_mut(), mock_env_drand(), info, msg).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
         // Verify Actions - request phase just saves pending state
         assert_eq!(res.attributes[0].value, "request_splice");
         assert_eq!(res.messages.len(), 0); // No messages in request phase
+        let splice_id = res.attributes[3].value.clone();
+
+        // Every slot of level 0 parents mutates, one way or the other
+        let mut config = CONFIG.load(deps.as_ref().storage).unwrap();
+        config.rules.splice_mutations[0] = SpliceMutation {
+            rot: 128,
+            primordial: 128,
+        };
+        CONFIG.save(deps.as_mut().storage, &config).unwrap();
+
+        let msg = ExecuteMsg::ResolveSplice { splice_id };
+        let res = execute(deps.as_mut(), mock_env(), message_info(&user, &[]), msg).unwrap();
+        let attr = |key: &str| -> usize {
+            res.attributes
+                .iter()
+                .find(|a| a.key == key)
+                .unwrap()
+                .value
+                .parse()
+                .unwrap()
+        };
+        assert_eq!(attr("mutations"), 8);
+
+        let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &res.messages[2].msg else {
+            panic!("Expected the child mint");
+        };
+        let spore_fates::cw721::ExecuteMsg::Mint { extension, .. } = from_json(msg).unwrap() else {
+            panic!("Expected the child mint");
+        };
+        let rot = extension.genes.iter().filter(|&&g| g == 0).count();
+        let primordial = extension.genes.iter().filter(|&&g| g == 4).count();
+        assert_eq!(attr("rot_mutations"), rot);
+        assert_eq!(attr("primordial_mutations"), primordial);
+        assert_eq!(rot + primordial, 8);
+
+        // The child is generation 1; its parents stay on as tombstones
+        assert_eq!(extension.generation, 1);
+        let child_id = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "child_id")
+            .unwrap()
+            .value
+            .clone();
+        let lineage: LineageResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Lineage {
+                    token_id: child_id.clone(),
+                    depth: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(lineage.record.generation, 1);
+        assert_eq!(lineage.record.parents, vec!["1", "2"]);
+        assert_eq!(
+            lineage.record.birth_round,
+            LATEST_ROUND + DEFAULT_MIN_ROUND_DELAY
+        );
+        assert_eq!(lineage.ancestors.len(), 2);
+        assert!(lineage.ancestors.iter().all(|entry| entry.record.burned));
+
+        let family: DescendantsResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Descendants {
+                    token_id: "2".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(family.descendants.len(), 1);
+        assert_eq!(family.descendants[0].token_id, child_id);
     }
 
     #[test]
@@ -2073,6 +5540,7 @@ mod tests {
             current_shares: Uint128::new(100),
             reward_debt: Uint128::zero(),
             pending_rewards: Uint128::new(500),
+            ascension_attempts: 0,
         };
         TOKEN_INFO
             .save(deps.as_mut().storage, "1", &token_info)
@@ -2084,7 +5552,7 @@ mod tests {
             .save(deps.as_mut().storage, &global_state)
             .unwrap();
 
-        let env = mock_env_drand();
+        let env = mock_env();
 
         let msg = ExecuteMsg::RequestAscend {
             token_id: "1".to_string(),
@@ -2116,6 +5584,7 @@ mod tests {
             current_shares: Uint128::new(100),
             reward_debt: Uint128::zero(),
             pending_rewards: Uint128::new(1000),
+            ascension_attempts: 0,
         };
         TOKEN_INFO
             .save(deps.as_mut().storage, "1", &token_info)
@@ -2137,6 +5606,8 @@ mod tests {
         // 3. Harvest
         let msg = ExecuteMsg::Harvest {
             token_id: "1".to_string(),
+            recipient: None,
+            msg: None,
         };
         let info = message_info(&user, &[]);
         let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -2180,8 +5651,12 @@ mod tests {
             base_cap: 0,
             base_stem: 0,
             base_spores: 0,
+            generation: 0,
         };
-        assert_eq!(calculate_shares(&traits), Uint128::new(1));
+        assert_eq!(
+            calculate_shares(&GameRules::default(), &traits),
+            Uint128::new(1)
+        );
 
         // Case 2: Positive traits
         // Sum = 2 + 1 + 3 = 6
@@ -2195,8 +5670,12 @@ mod tests {
             base_cap: 0,
             base_stem: 0,
             base_spores: 0,
+            generation: 0,
         };
-        assert_eq!(calculate_shares(&traits), Uint128::new(36));
+        assert_eq!(
+            calculate_shares(&GameRules::default(), &traits),
+            Uint128::new(36)
+        );
 
         // Case 3: With substrate multiplier
         // Sum = 1 + 1 + 1 = 3
@@ -2211,8 +5690,12 @@ mod tests {
             base_cap: 0,
             base_stem: 0,
             base_spores: 0,
+            generation: 0,
         };
-        assert_eq!(calculate_shares(&traits), Uint128::new(27));
+        assert_eq!(
+            calculate_shares(&GameRules::default(), &traits),
+            Uint128::new(27)
+        );
 
         // Case 4: Negative traits (Floor check)
         // Sum = -6 -> Max(1)
@@ -2226,8 +5709,12 @@ mod tests {
             base_cap: 0,
             base_stem: 0,
             base_spores: 0,
+            generation: 0,
         };
-        assert_eq!(calculate_shares(&traits), Uint128::new(1));
+        assert_eq!(
+            calculate_shares(&GameRules::default(), &traits),
+            Uint128::new(1)
+        );
     }
 
     #[test]
@@ -2246,6 +5733,7 @@ mod tests {
         let msg = ExecuteMsg::Spin {
             token_id: "1".to_string(),
             trait_target: TraitTarget::Cap,
+            pay_from_rewards: false,
         };
 
         // Send insufficient payment (500k vs 1M required)
@@ -2271,6 +5759,7 @@ mod tests {
         let msg = ExecuteMsg::Spin {
             token_id: "1".to_string(),
             trait_target: TraitTarget::Cap,
+            pay_from_rewards: false,
         };
 
         // Send wrong denom
@@ -2280,6 +5769,437 @@ mod tests {
         assert!(matches!(err, ContractError::InvalidPayment {}));
     }
 
+    #[test]
+    fn test_spin_refunds_excess_and_other_denoms() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+        let owner = deps.api.addr_make("owner");
+
+        setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+
+        let traits = TraitExtension::default();
+        mock_querier_with_nft(&mut deps.querier, &cw721, "1", &owner, traits);
+
+        let msg = ExecuteMsg::Spin {
+            token_id: "1".to_string(),
+            trait_target: TraitTarget::Cap,
+            pay_from_rewards: false,
+        };
+
+        // 1.5M sent for a 1M spin, plus a stray denom
+        let funds = vec![
+            Coin::new(1_500_000u128, PAYMENT_DENOM),
+            Coin::new(7u128, "uatom"),
+        ];
+        let info = message_info(&owner, &funds);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(
+            res.messages[0].msg,
+            cosmwasm_std::CosmosMsg::Bank(BankMsg::Send {
+                to_address: owner.to_string(),
+                amount: vec![
+                    Coin::new(7u128, "uatom"),
+                    Coin::new(500_000u128, PAYMENT_DENOM)
+                ],
+            })
+        );
+        let refund = res.attributes.iter().find(|a| a.key == "refund").unwrap();
+        assert_eq!(refund.value, format!("7uatom,500000{}", PAYMENT_DENOM));
+
+        // Only the price is booked as spin volume
+        let stats = GAME_STATS.load(deps.as_ref().storage).unwrap();
+        assert_eq!(stats.total_spin_volume, Uint128::new(1_000_000));
+    }
+
+    #[test]
+    fn test_mint_refunds() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let user = deps.api.addr_make("user");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+
+        // Free mints
+        setup_contract(deps.as_mut(), &creator, &cw721, &oracle).unwrap();
+        deps.querier.update_wasm(|query| match query {
+            CosmWasmQuery::Smart { msg, .. } => mock_oracle_query(msg),
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "skip".into(),
+            }),
+        });
+
+        // Exact (empty) payment: nothing to refund
+        let info = message_info(&user, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::RequestMint {}).unwrap();
+        assert!(res.messages.is_empty());
+        assert!(res.attributes.iter().all(|a| a.key != "refund"));
+
+        // Anything sent to a free mint comes back
+        let funds = vec![Coin::new(100u128, PAYMENT_DENOM), Coin::new(3u128, "uatom")];
+        let info = message_info(&user, &funds);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::RequestMint {}).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            cosmwasm_std::CosmosMsg::Bank(BankMsg::Send {
+                to_address: user.to_string(),
+                amount: vec![Coin::new(3u128, "uatom"), Coin::new(100u128, PAYMENT_DENOM)],
+            })
+        );
+    }
+
+    /// Instantiates with `token` as the cw20 payment; `into_checked` asks it for TokenInfo
+    fn setup_cw20_contract(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        creator: &Addr,
+        cw721: &Addr,
+        oracle: &Addr,
+        token: &Addr,
+    ) -> Result<Response, ContractError> {
+        deps.querier.update_wasm(|query| match query {
+            CosmWasmQuery::Smart { .. } => SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&cw20::TokenInfoResponse {
+                    name: "Shroom".to_string(),
+                    symbol: "SHRM".to_string(),
+                    decimals: 6,
+                    total_supply: Uint128::new(1_000_000_000),
+                })
+                .unwrap(),
+            )),
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "skip".into(),
+            }),
+        });
+
+        let msg = InstantiateMsg {
+            payment: UncheckedDenom::Cw20(token.to_string()),
+            spin_cost: Uint128::new(1_000_000),
+            mint_pricing: PricingCurve::Linear {
+                base: Uint128::zero(),
+                increment: Uint128::zero(),
+            },
+            cw721_addr: cw721.to_string(),
+            oracle_addr: oracle.to_string(),
+            min_round_delay: None,
+            max_block_time: None,
+            admin: None,
+            fee_split: None,
+            burn_addr: None,
+            epoch_length: None,
+            history_retention: None,
+            rules: None,
+        };
+        let info = message_info(creator, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg)
+    }
+
+    /// Balances of the payment token, moved the way the cw20 contract would move
+    /// them for a `Send` to the controller and the controller's `Transfer`s.
+    /// Stands in for cw20-base under multi-test until both are dev-dependencies.
+    #[derive(Default)]
+    struct Cw20Ledger {
+        balances: BTreeMap<Addr, u128>,
+    }
+
+    impl Cw20Ledger {
+        fn move_tokens(&mut self, from: &Addr, to: &Addr, amount: u128) {
+            let balance = self.balances.entry(from.clone()).or_default();
+            *balance = balance
+                .checked_sub(amount)
+                .expect("cw20 transfer over balance");
+            *self.balances.entry(to.clone()).or_default() += amount;
+        }
+
+        fn send(
+            &mut self,
+            from: &Addr,
+            controller: &Addr,
+            amount: u128,
+            msg: &ReceiveMsg,
+        ) -> ExecuteMsg {
+            self.move_tokens(from, controller, amount);
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: from.to_string(),
+                amount: Uint128::new(amount),
+                msg: to_json_binary(msg).unwrap(),
+            })
+        }
+
+        /// Execute the controller's token transfers; anything else in the payment
+        /// path (bank sends in particular) is a bug
+        fn apply(&mut self, res: &Response, controller: &Addr, token: &Addr) {
+            for sub_msg in &res.messages {
+                match &sub_msg.msg {
+                    CosmosMsg::Wasm(WasmMsg::Execute {
+                        contract_addr, msg, ..
+                    }) if contract_addr == token.as_str() => match from_json(msg).unwrap() {
+                        Cw20ExecuteMsg::Transfer { recipient, amount } => {
+                            self.move_tokens(controller, &Addr::unchecked(recipient), amount.u128())
+                        }
+                        other => panic!("unexpected cw20 message {:?}", other),
+                    },
+                    CosmosMsg::Bank(msg) => panic!("native payout {:?} in a cw20 game", msg),
+                    _ => {}
+                }
+            }
+        }
+
+        fn balance(&self, addr: &Addr) -> u128 {
+            self.balances.get(addr).cloned().unwrap_or_default()
+        }
+
+        fn total(&self) -> u128 {
+            self.balances.values().sum()
+        }
+    }
+
+    #[test]
+    fn test_cw20_balances_through_spin_and_harvest() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+        let token = deps.api.addr_make("token");
+        let owner = deps.api.addr_make("owner");
+        let burn = deps.api.addr_make("burn");
+        let controller = mock_env().contract.address;
+
+        setup_cw20_contract(&mut deps, &creator, &cw721, &oracle, &token).unwrap();
+        let mut config = CONFIG.load(deps.as_ref().storage).unwrap();
+        config.fee_split = FeeSplit {
+            treasury_bps: 1_000,
+            burn_bps: 1_000,
+            reward_bps: 8_000,
+        };
+        config.burn_addr = Some(burn.clone());
+        CONFIG.save(deps.as_mut().storage, &config).unwrap();
+
+        mock_querier_with_nft(
+            &mut deps.querier,
+            &cw721,
+            "1",
+            &owner,
+            TraitExtension {
+                genes: vec![0; 8],
+                ..TraitExtension::default()
+            },
+        );
+        TOKEN_INFO
+            .save(
+                deps.as_mut().storage,
+                "1",
+                &TokenInfo {
+                    current_shares: Uint128::new(100),
+                    reward_debt: Uint128::zero(),
+                    pending_rewards: Uint128::zero(),
+                    ascension_attempts: 0,
+                },
+            )
+            .unwrap();
+        let mut global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+        global_state.total_shares = Uint128::new(100);
+        GLOBAL_STATE
+            .save(deps.as_mut().storage, &global_state)
+            .unwrap();
+
+        let mut ledger = Cw20Ledger::default();
+        ledger.balances.insert(owner.clone(), 3_000_000);
+        let supply = ledger.total();
+
+        // Overpay a spin through the hook; the excess comes straight back
+        let spin = ReceiveMsg::Spin {
+            token_id: "1".to_string(),
+            trait_target: TraitTarget::Cap,
+        };
+        let msg = ledger.send(&owner, &controller, 1_500_000, &spin);
+        let res = execute(deps.as_mut(), mock_env(), message_info(&token, &[]), msg).unwrap();
+        ledger.apply(&res, &controller, &token);
+        assert_eq!(ledger.balance(&owner), 2_000_000);
+        assert_eq!(ledger.balance(&controller), 1_000_000);
+
+        // Resolution burns 10% and keeps 10% for the treasury
+        let resolve = ExecuteMsg::ResolveSpin {
+            token_id: "1".to_string(),
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&creator, &[]),
+            resolve,
+        )
+        .unwrap();
+        ledger.apply(&res, &controller, &token);
+        assert_eq!(ledger.balance(&burn), 100_000);
+
+        let harvest = ExecuteMsg::Harvest {
+            token_id: "1".to_string(),
+            recipient: None,
+            msg: None,
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&owner, &[]),
+            harvest,
+        )
+        .unwrap();
+        ledger.apply(&res, &controller, &token);
+        assert!(ledger.balance(&owner) > 2_000_000);
+
+        let withdraw = ExecuteMsg::WithdrawTreasury {
+            amount: None,
+            recipient: None,
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&creator, &[]),
+            withdraw,
+        )
+        .unwrap();
+        ledger.apply(&res, &controller, &token);
+        assert_eq!(ledger.balance(&creator), 100_000);
+
+        // Every token is accounted for, and the controller still holds what it owes
+        assert_eq!(ledger.total(), supply);
+        let global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+        let token_info = TOKEN_INFO.load(deps.as_ref().storage, "1").unwrap();
+        let owed = TREASURY.load(deps.as_ref().storage).unwrap().balance
+            + token_info.pending_rewards
+            + accrued_rewards(&token_info, global_state.global_reward_index).unwrap();
+        assert!(Uint128::new(ledger.balance(&controller)) >= owed);
+    }
+
+    #[test]
+    fn test_cw20_spin_via_receive() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+        let token = deps.api.addr_make("token");
+        let owner = deps.api.addr_make("owner");
+
+        setup_cw20_contract(&mut deps, &creator, &cw721, &oracle, &token).unwrap();
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        assert_eq!(config.payment, Denom::Cw20(token.clone()));
+
+        let traits = TraitExtension::default();
+        mock_querier_with_nft(&mut deps.querier, &cw721, "1", &owner, traits);
+
+        let receive = |amount: u128| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: owner.to_string(),
+                amount: Uint128::new(amount),
+                msg: to_json_binary(&ReceiveMsg::Spin {
+                    token_id: "1".to_string(),
+                    trait_target: TraitTarget::Cap,
+                })
+                .unwrap(),
+            })
+        };
+
+        // Tokens sent by anything but the configured cw20 are not a payment
+        let impostor = deps.api.addr_make("impostor");
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&impostor, &[]),
+            receive(1_000_000),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidPayment {}));
+
+        // Native funds can't pay for a cw20-priced spin
+        let spin = ExecuteMsg::Spin {
+            token_id: "1".to_string(),
+            trait_target: TraitTarget::Cap,
+            pay_from_rewards: false,
+        };
+        let info = message_info(&owner, &coins(1_000_000, PAYMENT_DENOM));
+        let err = execute(deps.as_mut(), mock_env(), info, spin).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidPayment {}));
+
+        // Overpaying through the hook refunds the excess as a cw20 transfer
+        let info = message_info(&token, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, receive(1_500_000)).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            cosmwasm_std::CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: token.to_string(),
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: owner.to_string(),
+                    amount: Uint128::new(500_000),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+        let refund = res.attributes.iter().find(|a| a.key == "refund").unwrap();
+        assert_eq!(refund.value, format!("500000{}", token));
+
+        let pending = PENDING_SPINS.load(deps.as_ref().storage, "1").unwrap();
+        assert_eq!(pending.player, owner);
+    }
+
+    #[test]
+    fn test_cw20_harvest_payout() {
+        let mut deps = mock_deps_custom();
+        let creator = deps.api.addr_make("creator");
+        let cw721 = deps.api.addr_make("cw721");
+        let oracle = deps.api.addr_make("oracle");
+        let token = deps.api.addr_make("token");
+        let user = deps.api.addr_make("user");
+
+        setup_cw20_contract(&mut deps, &creator, &cw721, &oracle, &token).unwrap();
+        mock_querier_with_nft(
+            &mut deps.querier,
+            &cw721,
+            "1",
+            &user,
+            TraitExtension::default(),
+        );
+
+        TOKEN_INFO
+            .save(
+                deps.as_mut().storage,
+                "1",
+                &TokenInfo {
+                    current_shares: Uint128::new(100),
+                    reward_debt: Uint128::zero(),
+                    pending_rewards: Uint128::new(1000),
+                    ascension_attempts: 0,
+                },
+            )
+            .unwrap();
+        let mut global_state = GLOBAL_STATE.load(deps.as_ref().storage).unwrap();
+        global_state.total_shares = Uint128::new(100);
+        GLOBAL_STATE
+            .save(deps.as_mut().storage, &global_state)
+            .unwrap();
+
+        let msg = ExecuteMsg::Harvest {
+            token_id: "1".to_string(),
+            recipient: None,
+            msg: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), message_info(&user, &[]), msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            cosmwasm_std::CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: token.to_string(),
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: user.to_string(),
+                    amount: Uint128::new(1000),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+    }
+
     #[test]
     fn test_spin_success() {
         let mut deps = mock_deps_custom();
@@ -2301,6 +6221,7 @@ mod tests {
             base_cap: 0,
             base_stem: 0,
             base_spores: 0,
+            generation: 0,
         };
 
         // 4. Register Mock NFT
@@ -2312,6 +6233,7 @@ mod tests {
             current_shares: Uint128::new(100), // Default linear shares for 0 stats
             reward_debt: Uint128::zero(),
             pending_rewards: Uint128::zero(),
+            ascension_attempts: 0,
         };
         TOKEN_INFO
             .save(deps.as_mut().storage, "1", &token_info)
@@ -2332,10 +6254,10 @@ mod tests {
         let msg = ExecuteMsg::Spin {
             token_id: "1".to_string(),
             trait_target: TraitTarget::Cap,
+            pay_from_rewards: false,
         };
 
-        let mut env = mock_env();
-        env.block.time = cosmwasm_std::Timestamp::from_seconds(1692803367 + 30);
+        let env = mock_env();
 
         let info = message_info(&owner, &coins(1_000_000, PAYMENT_DENOM));
         let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
@@ -2349,6 +6271,7 @@ mod tests {
         let res = query(deps.as_ref(), env.clone(), query_msg).unwrap();
         let pending: PendingSpinResponse = from_json(&res).unwrap();
         assert!(pending.is_pending);
+        assert_eq!(pending.target_round, LATEST_ROUND + DEFAULT_MIN_ROUND_DELAY);
 
         // ==========================================
         // PHASE 2: RESOLVE SPIN
@@ -2393,10 +6316,7 @@ mod tests {
                     }
                 } else if contract_addr == &oracle_str {
                     // MOCK ORACLE RANDOMNESS
-                    let resp = BeaconResponse {
-                        uniform_seed: [123u8; 32],
-                    };
-                    SystemResult::Ok(ContractResult::Ok(to_json_binary(&resp).unwrap()))
+                    mock_oracle_query(msg)
                 } else {
                     SystemResult::Err(SystemError::UnsupportedRequest {
                         kind: "unknown addr".into(),
@@ -2444,6 +6364,8 @@ mod tests {
 
         let msg = ExecuteMsg::Harvest {
             token_id: "1".to_string(),
+            recipient: None,
+            msg: None,
         };
 
         let info = message_info(&owner, &[]);
@@ -2474,12 +6396,15 @@ mod tests {
                     current_shares: Uint128::new(100),
                     reward_debt: Uint128::zero(),
                     pending_rewards: Uint128::zero(),
+                    ascension_attempts: 0,
                 },
             )
             .unwrap();
 
         let msg = ExecuteMsg::Harvest {
             token_id: "1".to_string(),
+            recipient: None,
+            msg: None,
         };
 
         let info = message_info(&owner, &[]);
@@ -2508,6 +6433,7 @@ mod tests {
             base_cap: 0,
             base_stem: 0,
             base_spores: 0,
+            generation: 0,
         };
         mock_querier_with_nft(&mut deps.querier, &cw721, "1", &owner, traits);
 
@@ -2541,6 +6467,7 @@ mod tests {
             base_cap: 0,
             base_stem: 0,
             base_spores: 0,
+            generation: 0,
         };
         mock_querier_with_nft(&mut deps.querier, &cw721, "1", &owner, traits);
 
@@ -2568,6 +6495,7 @@ mod tests {
             current_shares: Uint128::new(150),
             reward_debt: Uint128::new(50),
             pending_rewards: Uint128::new(100),
+            ascension_attempts: 0,
         };
         TOKEN_INFO
             .save(deps.as_mut().storage, "1", &token_info)
@@ -2586,7 +6514,6 @@ mod tests {
         assert_eq!(info.pending_rewards, Uint128::new(100));
     }
 
-
     #[test]
     fn test_leaderboard_mechanics() {
         let mut deps = mock_deps_custom();
@@ -0,0 +1,169 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use cosmwasm_std::{Empty, Order, StdError, StdResult, Storage};
+
+use crate::msg::LineageEntry;
+use crate::state::{LineageRecord, CHILDREN, LINEAGE, TOKEN_INFO};
+
+/// Generations of ancestors `Lineage` returns by default, and at most
+pub const DEFAULT_LINEAGE_DEPTH: u32 = 3;
+pub const MAX_LINEAGE_DEPTH: u32 = 5;
+
+/// Most entries `Descendants` returns
+pub const MAX_DESCENDANTS: usize = 100;
+
+/// Record of `token_id`. Mushrooms from before lineage tracking are founders of
+/// unknown birth.
+pub fn load(storage: &dyn Storage, token_id: &str) -> StdResult<Option<LineageRecord>> {
+    if let Some(record) = LINEAGE.may_load(storage, token_id)? {
+        return Ok(Some(record));
+    }
+    Ok(TOKEN_INFO.has(storage, token_id).then(|| LineageRecord {
+        parents: vec![],
+        generation: 0,
+        birth_round: 0,
+        burned: false,
+    }))
+}
+
+/// Record a mint or splice child; returns its generation
+pub fn record_birth(
+    storage: &mut dyn Storage,
+    token_id: &str,
+    parents: &[&str],
+    birth_round: u64,
+) -> StdResult<u32> {
+    let mut generation = 0;
+    for parent in parents {
+        let parent_generation = load(storage, parent)?.map_or(0, |record| record.generation);
+        generation = generation.max(parent_generation + 1);
+        CHILDREN.save(storage, (parent, token_id), &Empty {})?;
+    }
+
+    let record = LineageRecord {
+        parents: parents.iter().map(|parent| parent.to_string()).collect(),
+        generation,
+        birth_round,
+        burned: false,
+    };
+    LINEAGE.save(storage, token_id, &record)?;
+    Ok(generation)
+}
+
+/// Keep a tombstone of `token_id`; call before its token info is removed
+pub fn record_burn(storage: &mut dyn Storage, token_id: &str) -> StdResult<()> {
+    let mut record = load(storage, token_id)?.ok_or_else(|| StdError::not_found("lineage"))?;
+    record.burned = true;
+    LINEAGE.save(storage, token_id, &record)
+}
+
+/// Ancestors of `token_id` up to `depth` generations back, closest first
+pub fn ancestors(
+    storage: &dyn Storage,
+    token_id: &str,
+    depth: u32,
+) -> StdResult<Vec<LineageEntry>> {
+    let Some(record) = load(storage, token_id)? else {
+        return Err(StdError::not_found("lineage"));
+    };
+
+    let mut seen = BTreeSet::new();
+    let mut entries = vec![];
+    let mut queue: VecDeque<(String, u32)> = record
+        .parents
+        .into_iter()
+        .map(|parent| (parent, 1))
+        .collect();
+    while let Some((token_id, distance)) = queue.pop_front() {
+        if distance > depth || !seen.insert(token_id.clone()) {
+            continue;
+        }
+        let Some(record) = load(storage, &token_id)? else {
+            continue;
+        };
+        queue.extend(
+            record
+                .parents
+                .iter()
+                .map(|parent| (parent.clone(), distance + 1)),
+        );
+        entries.push(LineageEntry {
+            token_id,
+            distance,
+            record,
+        });
+    }
+    Ok(entries)
+}
+
+/// Descendants of `token_id`, closest first, at most `MAX_DESCENDANTS`
+pub fn descendants(storage: &dyn Storage, token_id: &str) -> StdResult<Vec<LineageEntry>> {
+    if load(storage, token_id)?.is_none() {
+        return Err(StdError::not_found("lineage"));
+    }
+
+    let mut seen = BTreeSet::new();
+    let mut entries = vec![];
+    let mut queue = VecDeque::from([(token_id.to_string(), 0u32)]);
+    while let Some((parent, distance)) = queue.pop_front() {
+        let children = CHILDREN
+            .prefix(&parent)
+            .keys(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<String>>>()?;
+        for child in children {
+            if entries.len() >= MAX_DESCENDANTS {
+                return Ok(entries);
+            }
+            if !seen.insert(child.clone()) {
+                continue;
+            }
+            let record = LINEAGE.load(storage, &child)?;
+            entries.push(LineageEntry {
+                token_id: child.clone(),
+                distance: distance + 1,
+                record,
+            });
+            queue.push_back((child, distance + 1));
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+
+    #[test]
+    fn family_tree() {
+        let mut storage = MockStorage::new();
+        record_birth(&mut storage, "1", &[], 10).unwrap();
+        record_birth(&mut storage, "2", &[], 11).unwrap();
+        record_birth(&mut storage, "3", &[], 12).unwrap();
+        assert_eq!(record_birth(&mut storage, "4", &["1", "2"], 20).unwrap(), 1);
+        record_burn(&mut storage, "1").unwrap();
+        record_burn(&mut storage, "2").unwrap();
+        // A generation 1 and a generation 0 parent
+        assert_eq!(record_birth(&mut storage, "5", &["4", "3"], 30).unwrap(), 2);
+
+        let tree = ancestors(&storage, "5", DEFAULT_LINEAGE_DEPTH).unwrap();
+        let ids: Vec<(&str, u32)> = tree
+            .iter()
+            .map(|entry| (entry.token_id.as_str(), entry.distance))
+            .collect();
+        assert_eq!(ids, vec![("4", 1), ("3", 1), ("1", 2), ("2", 2)]);
+        // Burned ancestors stay on as tombstones
+        assert!(tree[2].record.burned);
+        assert_eq!(tree[2].record.birth_round, 10);
+        assert_eq!(ancestors(&storage, "5", 1).unwrap().len(), 2);
+
+        let family = descendants(&storage, "1").unwrap();
+        let ids: Vec<(&str, u32)> = family
+            .iter()
+            .map(|entry| (entry.token_id.as_str(), entry.distance))
+            .collect();
+        assert_eq!(ids, vec![("4", 1), ("5", 2)]);
+        assert!(descendants(&storage, "5").unwrap().is_empty());
+        assert!(ancestors(&storage, "9", 1).is_err());
+    }
+}
@@ -1,17 +1,44 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Decimal, Uint128, Uint64};
+use cosmwasm_std::{Binary, Decimal, SignedDecimal, Timestamp, Uint128, Uint64};
+use cw20::{Cw20ReceiveMsg, UncheckedDenom};
 use spore_fates::game::GlobalBiomass;
 
-use crate::state::LeaderboardEntry;
+use crate::emissions::EmissionSchedule;
+use crate::fees::FeeSplit;
+use crate::pricing::PricingCurve;
+use crate::rules::GameRules;
+use crate::state::{EcosystemSnapshot, LeaderboardEntry, LineageRecord};
 
 #[cw_serde]
 pub struct InstantiateMsg {
-    pub payment_denom: String,
+    /// Native/tokenfactory denom or cw20 token used for all payments and payouts
+    pub payment: UncheckedDenom,
     pub spin_cost: Uint128,
-    pub mint_cost: Uint128,
-    pub mint_cost_increment: Uint128,
+    pub mint_pricing: PricingCurve,
     pub oracle_addr: String,
     pub cw721_addr: String,
+    /// Rounds between the latest public drand round and a request's target (default 2, min 1)
+    pub min_round_delay: Option<u64>,
+    /// Seconds a request's block time may lag behind real time (default 0)
+    pub max_block_time: Option<u64>,
+    /// Defaults to the instantiator
+    pub admin: Option<String>,
+    /// Defaults to everything going to the reward pool
+    pub fee_split: Option<FeeSplit>,
+    /// Required when `fee_split` burns a share
+    pub burn_addr: Option<String>,
+    /// Seconds between Sun Line snapshots (default one day)
+    pub epoch_length: Option<u64>,
+    /// Epochs of ecosystem history to keep (default 30)
+    pub history_retention: Option<u64>,
+    /// Defaults to the launch balancing
+    pub rules: Option<GameRules>,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    /// Required when migrating from the first release, which had no admin
+    pub admin: Option<String>,
 }
 
 #[cw_serde]
@@ -26,12 +53,21 @@ pub enum ExecuteMsg {
     Spin {
         token_id: String,
         trait_target: TraitTarget,
+        /// Pay the cost out of the mushroom's pending rewards at face value,
+        /// without the canopy multiplier a harvest would apply
+        #[serde(default)]
+        pay_from_rewards: bool,
     },
     ResolveSpin {
         token_id: String,
     },
     Harvest {
         token_id: String,
+        /// Defaults to the sender
+        recipient: Option<String>,
+        /// Executed on `recipient` with the payout attached (a cw20 `Send` when
+        /// payments are in a cw20 token)
+        msg: Option<Binary>,
     },
     RequestAscend {
         token_id: String,
@@ -43,6 +79,8 @@ pub enum ExecuteMsg {
     ResolveMint {
         mint_id: String,
     },
+    /// Burn a mushroom, paying out its rewards after the canopy multiplier plus
+    /// the compost refund
     Recycle {
         token_id: String,
     },
@@ -53,12 +91,64 @@ pub enum ExecuteMsg {
     ResolveSplice {
         splice_id: String,
     },
+    /// Re-roll one genome slot. Costs the spin cost for the substrate times the
+    /// multiplier of the gene being replaced.
+    RequestGeneEdit {
+        token_id: String,
+        slot: u8,
+    },
+    ResolveGeneEdit {
+        token_id: String,
+    },
     AcceptMinterOwnership {
         cw721_contract: String,
     },
     AcceptCreatorOwnership {
         cw721_contract: String,
     },
+    /// cw20 payments; `msg` is a `ReceiveMsg`
+    Receive(Cw20ReceiveMsg),
+    /// Admin only. Withdraws `amount` (default: all) of the treasury balance
+    /// to `recipient` (default: the admin).
+    WithdrawTreasury {
+        amount: Option<Uint128>,
+        recipient: Option<String>,
+    },
+    /// Admin only. Replaces how resolved payments are split; `burn_addr`
+    /// replaces the burn address when given.
+    UpdateFeeSplit {
+        fee_split: FeeSplit,
+        burn_addr: Option<String>,
+    },
+    /// Adds the attached payment to the emission reserve
+    FundEmissions {},
+    /// Admin only. Starts a new schedule now; everything emitted so far is
+    /// accrued at the old rate first.
+    UpdateEmissionSchedule {
+        rate_per_second: Uint128,
+        /// Seconds between halvings; `None` keeps the rate constant
+        halving_interval: Option<u64>,
+    },
+    /// Admin only. Replaces the balancing constants; shares already held keep
+    /// their value until the mushroom's next spin, harvest or ascension.
+    UpdateGameRules {
+        rules: GameRules,
+    },
+}
+
+/// Paid actions that can be embedded in a cw20 `Send`
+#[cw_serde]
+pub enum ReceiveMsg {
+    Spin {
+        token_id: String,
+        trait_target: TraitTarget,
+    },
+    RequestMint {},
+    FundEmissions {},
+    RequestGeneEdit {
+        token_id: String,
+        slot: u8,
+    },
 }
 
 #[cw_serde]
@@ -74,6 +164,10 @@ pub enum QueryMsg {
     },
     GetGameStats {},
     GetCurrentMintPrice {},
+    /// Total cost of the next `quantity` mints (at most 100)
+    QuoteMint {
+        quantity: u64,
+    },
     GetPlayerProfile {
         address: String,
     },
@@ -89,7 +183,44 @@ pub enum QueryMsg {
     GetPendingAscend {
         token_id: String,
     },
+    GetPendingGeneEdit {
+        token_id: String,
+    },
     GetLeaderboard {},
+    /// Protocol fees held and paid out so far
+    AccruedFees {},
+    EmissionSchedule {},
+    /// Sun Line of the current canopy epoch
+    SunLine {},
+    /// Snapshots taken at the start of each epoch, oldest first
+    EcosystemHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    GameRules {},
+    /// Odds of every outcome of splicing two mushrooms
+    PreviewSplice {
+        parent_1_id: String,
+        parent_2_id: String,
+    },
+    /// Odds of every result of spinning `trait_target` now
+    SimulateSpin {
+        token_id: String,
+        trait_target: TraitTarget,
+    },
+    /// Odds of the next ascension attempt
+    SimulateAscend {
+        token_id: String,
+    },
+    /// Ancestors up to `depth` generations back (default 3, at most 5)
+    Lineage {
+        token_id: String,
+        depth: Option<u32>,
+    },
+    /// Children, grandchildren and so on, closest first (at most 100)
+    Descendants {
+        token_id: String,
+    },
 }
 
 #[cw_serde]
@@ -102,6 +233,17 @@ pub struct PendingRewardsResponse {
     pub accumulated_rewards: Uint128, // The "Raw" amount (Hidden/Potential)
     pub canopy_multiplier: Decimal,   // The current weather (0.0 to 5.0)
     pub estimated_payout: Uint128,    // What you get if you harvest NOW
+    /// Part of `accumulated_rewards` from emissions not yet written to the index
+    pub projected_emissions: Uint128,
+}
+
+#[cw_serde]
+pub struct EmissionScheduleResponse {
+    pub schedule: EmissionSchedule,
+    pub current_rate: Uint128,
+    pub reserve: Uint128,
+    pub total_emitted: Uint128,
+    pub last_accrued: Timestamp,
 }
 
 #[cw_serde]
@@ -110,6 +252,25 @@ pub struct EcosystemMetricsResponse {
     pub cap_multiplier: Decimal,
     pub stem_multiplier: Decimal,
     pub spores_multiplier: Decimal,
+    /// Biomass averaged since the start of the previous epoch; harvests pay on this
+    pub time_weighted_biomass: GlobalBiomass,
+}
+
+#[cw_serde]
+pub struct SunLineResponse {
+    pub epoch: u64,
+    pub started_at: Timestamp,
+    pub next_epoch_at: Timestamp,
+    /// Average canopy score of active mushrooms at the start of the epoch
+    pub sun_line: Decimal,
+    /// Scores below this earn nothing (80% of the Sun Line)
+    pub shadow_line: Decimal,
+    pub active_mushrooms: u64,
+}
+
+#[cw_serde]
+pub struct EcosystemHistoryResponse {
+    pub snapshots: Vec<EcosystemSnapshot>,
 }
 
 #[cw_serde]
@@ -126,6 +287,7 @@ pub struct GameStatsResponse {
     pub total_harvests: u64,
     pub total_splices: u64,
     pub total_ascensions: u64,
+    pub total_gene_edits: u64,
 }
 
 #[cw_serde]
@@ -133,6 +295,25 @@ pub struct MintPriceResponse {
     pub price: Uint128,
 }
 
+#[cw_serde]
+pub struct MintQuoteResponse {
+    pub quantity: u64,
+    pub total_cost: Uint128,
+    /// Price of each mint, in order
+    pub prices: Vec<Uint128>,
+}
+
+#[cw_serde]
+pub struct AccruedFeesResponse {
+    pub fee_split: FeeSplit,
+    /// Available to withdraw
+    pub treasury_balance: Uint128,
+    pub total_accrued: Uint128,
+    pub total_withdrawn: Uint128,
+    pub total_burned: Uint128,
+    pub total_composted: Uint128,
+}
+
 #[cw_serde]
 pub struct PlayerProfileResponse {
     pub total_mushrooms: u64,
@@ -159,15 +340,131 @@ pub struct PendingSpliceResponse {
     pub target_round: u64,
 }
 
+#[cw_serde]
+pub struct PendingGeneEditResponse {
+    pub is_pending: bool,
+    pub target_round: u64,
+    pub slot: u8,
+}
+
 #[cw_serde]
 pub struct PendingAscendResponse {
     pub is_pending: bool,
     pub target_round: u64,
+    /// Failed ascensions since the last success
+    pub ascension_attempts: u32,
+    /// Chance of the pending (or next) attempt, pity included
+    pub success_chance_bps: u16,
+}
+
+/// Chance of each gene in one of the child's slots
+#[cw_serde]
+pub struct GeneOdds {
+    pub rot: Decimal,
+    pub cap: Decimal,
+    pub stem: Decimal,
+    pub spores: Decimal,
+    pub primordial: Decimal,
+}
+
+#[cw_serde]
+pub struct StatTierOdds {
+    pub bonus: u8,
+    pub chance: Decimal,
+}
+
+#[cw_serde]
+pub struct SubstrateOdds {
+    pub substrate: u8,
+    pub chance: Decimal,
+}
+
+#[cw_serde]
+pub struct SplicePreviewResponse {
+    /// One entry per gene slot of the child
+    pub slots: Vec<GeneOdds>,
+    /// Odds of each base stat bonus, lowest first
+    pub base_cap: Vec<StatTierOdds>,
+    pub base_stem: Vec<StatTierOdds>,
+    pub base_spores: Vec<StatTierOdds>,
+    /// Synergy bonus included
+    pub substrate: Vec<SubstrateOdds>,
+    /// Mean shares of the child, before any spins
+    pub expected_shares: Uint128,
+}
+
+#[cw_serde]
+pub struct TraitValueOdds {
+    pub value: i8,
+    pub chance: Decimal,
+}
+
+/// Shares and emissions a mushroom has now and can expect after an action
+#[cw_serde]
+pub struct ShareEstimate {
+    pub current_shares: Uint128,
+    pub expected_shares: Decimal,
+    pub expected_share_change: SignedDecimal,
+    /// Emissions per second at the current rate, after the canopy multiplier
+    pub current_reward_rate: Decimal,
+    pub expected_reward_rate: Decimal,
+}
+
+#[cw_serde]
+pub struct SpinSimulationResponse {
+    pub current_value: i8,
+    pub success_chance: Decimal,
+    /// Each resulting value of the target trait, lowest first
+    pub outcomes: Vec<TraitValueOdds>,
+    pub cost: Uint128,
+    pub estimate: ShareEstimate,
+}
+
+#[cw_serde]
+pub struct AscendSimulationResponse {
+    pub current_substrate: u8,
+    pub success_chance: Decimal,
+    /// Cap, stem and spores reset to 0 on success, and on failure if the rules say so
+    pub outcomes: Vec<SubstrateOdds>,
+    /// Pending rewards a success burns
+    pub cost: Uint128,
+    /// Pending rewards a failure burns
+    pub failure_cost: Uint128,
+    pub estimate: ShareEstimate,
+}
+
+#[cw_serde]
+pub struct LineageEntry {
+    pub token_id: String,
+    /// Generations away from the queried mushroom
+    pub distance: u32,
+    pub record: LineageRecord,
+}
+
+#[cw_serde]
+pub struct LineageResponse {
+    pub token_id: String,
+    pub record: LineageRecord,
+    /// Closest first; burned ancestors included
+    pub ancestors: Vec<LineageEntry>,
+}
+
+#[cw_serde]
+pub struct DescendantsResponse {
+    pub descendants: Vec<LineageEntry>,
 }
 
 #[cw_serde]
 pub enum OracleQueryMsg {
     Beacon { round: Uint64 },
+    RoundAt { time: Timestamp },
+    TimeOf { round: Uint64 },
+}
+
+#[cw_serde]
+pub struct RoundResponse {
+    pub round: Uint64,
+    pub time: Timestamp,
 }
 
 #[cw_serde]
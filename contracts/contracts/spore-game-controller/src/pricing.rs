@@ -0,0 +1,242 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    Decimal256, OverflowError, OverflowOperation, StdError, StdResult, Uint128, Uint256, Uint512,
+};
+
+use crate::error::ContractError;
+use crate::rules::BPS;
+
+/// Upper bound for `QuoteMint { quantity }`
+pub const MAX_QUOTE_QUANTITY: u64 = 100;
+
+/// Mint price as a function of the active supply (minted minus burned)
+#[cw_serde]
+pub enum PricingCurve {
+    /// `base + increment * supply`
+    Linear { base: Uint128, increment: Uint128 },
+    /// `base * (1 + growth_bps / 10000) ^ supply`
+    Exponential { base: Uint128, growth_bps: u32 },
+    /// Rises smoothly from `min` to `max`, passing the halfway price at `midpoint`.
+    /// `width` is the supply distance from the midpoint at which the price is a
+    /// quarter of the way between the ends; larger values give a gentler slope.
+    Sigmoid {
+        min: Uint128,
+        max: Uint128,
+        midpoint: u64,
+        width: u64,
+    },
+    /// Linear segments; each applies from its `from_supply` until the next one starts
+    Piecewise { segments: Vec<PriceSegment> },
+}
+
+#[cw_serde]
+pub struct PriceSegment {
+    pub from_supply: u64,
+    pub base: Uint128,
+    pub increment: Uint128,
+}
+
+impl PricingCurve {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        let invalid = |msg: &str| {
+            Err(ContractError::InvalidPricingCurve {
+                msg: msg.to_string(),
+            })
+        };
+
+        match self {
+            PricingCurve::Linear { .. } | PricingCurve::Exponential { .. } => Ok(()),
+            PricingCurve::Sigmoid {
+                min, max, width, ..
+            } => {
+                if min > max {
+                    return invalid("min must not exceed max");
+                }
+                if *width == 0 {
+                    return invalid("width must be at least 1");
+                }
+                Ok(())
+            }
+            PricingCurve::Piecewise { segments } => {
+                if segments.first().map(|s| s.from_supply) != Some(0) {
+                    return invalid("first segment must start at supply 0");
+                }
+                if segments
+                    .windows(2)
+                    .any(|pair| pair[0].from_supply >= pair[1].from_supply)
+                {
+                    return invalid("segments must start at increasing supplies");
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Price of the next mint when `supply` mushrooms are alive
+    pub fn price(&self, supply: u64) -> StdResult<Uint128> {
+        match self {
+            PricingCurve::Linear { base, increment } => linear(*base, *increment, supply),
+            PricingCurve::Exponential { base, growth_bps } => {
+                let exponent = u32::try_from(supply).map_err(|_| overflow())?;
+                let factor = Decimal256::from_ratio(BPS as u64 + *growth_bps as u64, BPS)
+                    .checked_pow(exponent)
+                    .map_err(|_| overflow())?;
+                let price = Uint256::from(*base)
+                    .checked_mul_floor(factor)
+                    .map_err(|_| overflow())?;
+                Uint128::try_from(price).map_err(|_| overflow())
+            }
+            PricingCurve::Sigmoid {
+                min,
+                max,
+                midpoint,
+                width,
+            } => {
+                // Algebraic sigmoid: f(d) = (1 + d / (|d| + width)) / 2, with d = supply - midpoint
+                let distance = supply.abs_diff(*midpoint) as u128;
+                let denominator = 2 * (distance + *width as u128);
+                let numerator = if supply >= *midpoint {
+                    2 * distance + *width as u128
+                } else {
+                    *width as u128
+                };
+                let span = Uint512::from(*max - *min);
+                let rise = span * Uint512::from(numerator) / Uint512::from(denominator);
+                Ok(*min + Uint128::try_from(rise).map_err(|_| overflow())?)
+            }
+            PricingCurve::Piecewise { segments } => {
+                let segment = segments
+                    .iter()
+                    .rev()
+                    .find(|segment| segment.from_supply <= supply)
+                    .ok_or_else(|| StdError::generic_err("no pricing segment for supply"))?;
+                linear(
+                    segment.base,
+                    segment.increment,
+                    supply - segment.from_supply,
+                )
+            }
+        }
+    }
+
+    /// Prices of the next `quantity` mints, assuming none are burned in between
+    pub fn quote(&self, supply: u64, quantity: u64) -> StdResult<Vec<Uint128>> {
+        (0..quantity)
+            .map(|i| self.price(supply.saturating_add(i)))
+            .collect()
+    }
+}
+
+fn linear(base: Uint128, increment: Uint128, steps: u64) -> StdResult<Uint128> {
+    Ok(increment
+        .checked_mul(Uint128::from(steps))?
+        .checked_add(base)?)
+}
+
+fn overflow() -> StdError {
+    OverflowError::new(OverflowOperation::Mul).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prices(curve: &PricingCurve, supplies: &[u64]) -> Vec<u128> {
+        supplies
+            .iter()
+            .map(|s| curve.price(*s).unwrap().u128())
+            .collect()
+    }
+
+    #[test]
+    fn linear_matches_previous_formula() {
+        let curve = PricingCurve::Linear {
+            base: Uint128::new(100),
+            increment: Uint128::new(10),
+        };
+        assert_eq!(prices(&curve, &[0, 1, 5]), vec![100, 110, 150]);
+    }
+
+    #[test]
+    fn exponential_compounds_in_basis_points() {
+        let curve = PricingCurve::Exponential {
+            base: Uint128::new(1_000_000),
+            growth_bps: 1_000,
+        };
+        assert_eq!(
+            prices(&curve, &[0, 1, 2, 10]),
+            vec![1_000_000, 1_100_000, 1_210_000, 2_593_742]
+        );
+
+        // Overflow is an error, not a wrapped price
+        assert!(curve.price(10_000).is_err());
+    }
+
+    #[test]
+    fn sigmoid_is_bounded_and_monotonic() {
+        let curve = PricingCurve::Sigmoid {
+            min: Uint128::new(100),
+            max: Uint128::new(900),
+            midpoint: 50,
+            width: 10,
+        };
+        assert_eq!(prices(&curve, &[40, 50, 60]), vec![300, 500, 700]);
+        assert_eq!(curve.price(0).unwrap(), Uint128::new(166));
+
+        let all = prices(&curve, &(0..200).collect::<Vec<_>>());
+        assert!(all.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(all.iter().all(|p| (100..900).contains(p)));
+    }
+
+    #[test]
+    fn piecewise_uses_active_segment() {
+        let curve = PricingCurve::Piecewise {
+            segments: vec![
+                PriceSegment {
+                    from_supply: 0,
+                    base: Uint128::new(100),
+                    increment: Uint128::zero(),
+                },
+                PriceSegment {
+                    from_supply: 10,
+                    base: Uint128::new(200),
+                    increment: Uint128::new(5),
+                },
+            ],
+        };
+        curve.validate().unwrap();
+        assert_eq!(prices(&curve, &[0, 9, 10, 12]), vec![100, 100, 200, 210]);
+
+        let unordered = PricingCurve::Piecewise {
+            segments: vec![
+                PriceSegment {
+                    from_supply: 0,
+                    base: Uint128::new(100),
+                    increment: Uint128::zero(),
+                },
+                PriceSegment {
+                    from_supply: 0,
+                    base: Uint128::new(200),
+                    increment: Uint128::zero(),
+                },
+            ],
+        };
+        assert!(unordered.validate().is_err());
+        assert!(PricingCurve::Piecewise { segments: vec![] }
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn quote_sums_consecutive_prices() {
+        let curve = PricingCurve::Linear {
+            base: Uint128::new(100),
+            increment: Uint128::new(10),
+        };
+        let quote = curve.quote(2, 3).unwrap();
+        assert_eq!(
+            quote,
+            vec![Uint128::new(120), Uint128::new(130), Uint128::new(140)]
+        );
+    }
+}
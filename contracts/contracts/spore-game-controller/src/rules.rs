@@ -0,0 +1,334 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Decimal, Uint128};
+use spore_fates::cw721::MAX_SUBSTRATE;
+
+use crate::error::ContractError;
+use crate::msg::TraitTarget;
+use crate::splice::GENE_KINDS;
+
+/// Substrate levels 0 to 5 (Overmind); the substrate tables have one entry each
+pub const SUBSTRATE_LEVELS: usize = MAX_SUBSTRATE as usize + 1;
+
+/// Basis points in 100%
+pub const BPS: u16 = 10_000;
+
+/// Relative target sizes of the three traits in the biomass
+#[cw_serde]
+pub struct TraitWeights {
+    pub cap: u32,
+    pub stem: u32,
+    pub spores: u32,
+}
+
+/// Chance of a child's gene slot mutating instead of inheriting, out of 256
+#[cw_serde]
+pub struct SpliceMutation {
+    pub rot: u8,
+    pub primordial: u8,
+}
+
+/// Balancing constants for the canopy, shares, spins and ascension
+#[cw_serde]
+pub struct GameRules {
+    /// Multiplier of a trait with no biomass at all
+    pub max_scarcity_multiplier: Decimal,
+    /// Scores below this fraction of the Sun Line are in the Shadow Zone
+    pub shadow_zone: Decimal,
+    /// A trait's multiplier is its target share of the biomass over its actual share
+    pub target_weights: TraitWeights,
+    /// Share multiplier by substrate level
+    pub share_multipliers: Vec<u64>,
+    /// Spin cost multiplier by substrate level
+    pub spin_cost_multipliers: Vec<u64>,
+    /// Lowest random byte that wins a spin, by substrate level
+    pub spin_success_thresholds: Vec<u8>,
+    pub ascend_chance_bps: u16,
+    /// Added to the ascend chance for every failed attempt since the last success
+    #[serde(default)]
+    pub ascend_pity_bps: u16,
+    /// This attempt since the last success always succeeds; 0 never guarantees one
+    #[serde(default)]
+    pub ascend_guaranteed_attempt: u32,
+    /// Share of the pending rewards a failed ascension recycles into the reward
+    /// pool; the rest stays pending. A success always takes all of them.
+    #[serde(default = "default_ascend_failure_burn_bps")]
+    pub ascend_failure_burn_bps: u16,
+    /// Whether a failed ascension resets cap, stem and spores to 0
+    #[serde(default = "default_ascend_failure_resets_stats")]
+    pub ascend_failure_resets_stats: bool,
+    /// Share of the current mint price refunded from the treasury on recycle
+    #[serde(default)]
+    pub compost_refund_bps: u16,
+    /// Splice mutation rates by the parents' average substrate level
+    #[serde(default = "default_splice_mutations")]
+    pub splice_mutations: Vec<SpliceMutation>,
+    /// Gene edit cost multiplier (on top of the spin cost) by the gene being
+    /// replaced, Rot (0) through Primordial (4)
+    #[serde(default = "default_gene_edit_multipliers")]
+    pub gene_edit_multipliers: Vec<u64>,
+}
+
+/// Rot is cheap to replace, Primordial costly
+fn default_gene_edit_multipliers() -> Vec<u64> {
+    vec![1, 2, 2, 2, 5]
+}
+
+/// Mutation risk climbs from ~5% for level 0 parents to ~20% for level 4
+fn default_splice_mutations() -> Vec<SpliceMutation> {
+    [11, 20, 30, 39, 49, 49]
+        .into_iter()
+        .map(|rot| SpliceMutation { rot, primordial: 2 })
+        .collect()
+}
+
+/// Failure used to cost everything
+fn default_ascend_failure_burn_bps() -> u16 {
+    BPS
+}
+
+fn default_ascend_failure_resets_stats() -> bool {
+    true
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        GameRules {
+            max_scarcity_multiplier: Decimal::from_ratio(5u128, 1u128),
+            shadow_zone: Decimal::percent(80),
+            target_weights: TraitWeights {
+                cap: 1,
+                stem: 1,
+                spores: 1,
+            },
+            share_multipliers: vec![1, 2, 3, 4, 5, 8],
+            // Overmind costs 20x to spin (High Risk/Reward)
+            spin_cost_multipliers: vec![1, 2, 3, 5, 10, 20],
+            spin_success_thresholds: vec![128, 128, 128, 140, 140, 140],
+            ascend_chance_bps: 2_000,
+            // 20%, 30%, 40%, 50%, then certain
+            ascend_pity_bps: 1_000,
+            ascend_guaranteed_attempt: 5,
+            ascend_failure_burn_bps: default_ascend_failure_burn_bps(),
+            ascend_failure_resets_stats: default_ascend_failure_resets_stats(),
+            compost_refund_bps: 0,
+            splice_mutations: default_splice_mutations(),
+            gene_edit_multipliers: default_gene_edit_multipliers(),
+        }
+    }
+}
+
+fn invalid(msg: &str) -> ContractError {
+    ContractError::InvalidGameRules {
+        msg: msg.to_string(),
+    }
+}
+
+/// Entry for `substrate`; anything past the table uses the last level
+fn by_level<T: Copy>(table: &[T], substrate: u8) -> T {
+    table[(substrate as usize).min(table.len() - 1)]
+}
+
+impl GameRules {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if self.max_scarcity_multiplier < Decimal::one() {
+            return Err(invalid("max scarcity multiplier is below 1"));
+        }
+        if self.shadow_zone > Decimal::one() {
+            return Err(invalid("shadow zone is above the Sun Line"));
+        }
+        let weights = &self.target_weights;
+        if weights.cap == 0 || weights.stem == 0 || weights.spores == 0 {
+            return Err(invalid("every trait needs a target weight"));
+        }
+        if self.share_multipliers.len() != SUBSTRATE_LEVELS
+            || self.spin_cost_multipliers.len() != SUBSTRATE_LEVELS
+            || self.spin_success_thresholds.len() != SUBSTRATE_LEVELS
+            || self.splice_mutations.len() != SUBSTRATE_LEVELS
+        {
+            return Err(invalid("substrate tables need one entry per level"));
+        }
+        if self.gene_edit_multipliers.len() != GENE_KINDS {
+            return Err(invalid("gene edit table needs one entry per gene"));
+        }
+        if self.share_multipliers.contains(&0)
+            || self.spin_cost_multipliers.contains(&0)
+            || self.gene_edit_multipliers.contains(&0)
+        {
+            return Err(invalid("multipliers must be at least 1"));
+        }
+        if self.ascend_chance_bps > BPS || self.ascend_pity_bps > BPS {
+            return Err(invalid("ascend chance is above 100%"));
+        }
+        if self.ascend_failure_burn_bps > BPS {
+            return Err(invalid("ascend failure burn is above 100%"));
+        }
+        if self.compost_refund_bps > BPS {
+            return Err(invalid("compost refund is above 100%"));
+        }
+        if self
+            .splice_mutations
+            .iter()
+            .any(|m| m.rot as u16 + m.primordial as u16 > 256)
+        {
+            return Err(invalid("splice mutation chance is above 100%"));
+        }
+        Ok(())
+    }
+
+    pub fn share_multiplier(&self, substrate: u8) -> u128 {
+        by_level(&self.share_multipliers, substrate) as u128
+    }
+
+    pub fn spin_cost_multiplier(&self, substrate: u8) -> u128 {
+        by_level(&self.spin_cost_multipliers, substrate) as u128
+    }
+
+    /// Unknown gene ids cost as much as Primordial
+    pub fn gene_edit_multiplier(&self, gene: u8) -> u128 {
+        by_level(&self.gene_edit_multipliers, gene) as u128
+    }
+
+    pub fn spin_success_threshold(&self, substrate: u8) -> u8 {
+        by_level(&self.spin_success_thresholds, substrate)
+    }
+
+    pub fn splice_mutation(&self, parent_1: u8, parent_2: u8) -> &SpliceMutation {
+        let average = ((parent_1 as u16 + parent_2 as u16) / 2) as usize;
+        &self.splice_mutations[average.min(self.splice_mutations.len() - 1)]
+    }
+
+    /// Share of the biomass `target` should have in a balanced ecosystem
+    pub fn target_share(&self, target: &TraitTarget) -> Decimal {
+        let weights = &self.target_weights;
+        let weight = match target {
+            TraitTarget::Cap => weights.cap,
+            TraitTarget::Stem => weights.stem,
+            TraitTarget::Spores => weights.spores,
+        };
+        let total = weights.cap as u128 + weights.stem as u128 + weights.spores as u128;
+        Decimal::from_ratio(weight, total)
+    }
+
+    /// Refund for recycling while the mint price is `price`
+    pub fn compost_refund(&self, price: Uint128) -> Uint128 {
+        price.multiply_ratio(self.compost_refund_bps, BPS)
+    }
+
+    /// Chance of an ascension after `failed_attempts` failures in a row
+    pub fn ascend_chance(&self, failed_attempts: u32) -> u16 {
+        if self.ascend_guaranteed_attempt != 0
+            && failed_attempts >= self.ascend_guaranteed_attempt - 1
+        {
+            return BPS;
+        }
+        let pity = (self.ascend_pity_bps as u32).saturating_mul(failed_attempts);
+        pity.saturating_add(self.ascend_chance_bps as u32)
+            .min(BPS as u32) as u16
+    }
+
+    /// Part of `pending` a failed ascension recycles
+    pub fn ascend_failure_burn(&self, pending: Uint128) -> Uint128 {
+        pending.multiply_ratio(self.ascend_failure_burn_bps, BPS)
+    }
+
+    pub fn ascend_succeeds(&self, random: u32, failed_attempts: u32) -> bool {
+        random % (BPS as u32) < self.ascend_chance(failed_attempts) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validation() {
+        assert!(GameRules::default().validate().is_ok());
+
+        let short_table = GameRules {
+            share_multipliers: vec![1, 2, 3],
+            ..GameRules::default()
+        };
+        assert!(short_table.validate().is_err());
+
+        let no_spores = GameRules {
+            target_weights: TraitWeights {
+                cap: 1,
+                stem: 1,
+                spores: 0,
+            },
+            ..GameRules::default()
+        };
+        assert!(no_spores.validate().is_err());
+
+        let certain = GameRules {
+            ascend_chance_bps: 10_001,
+            ..GameRules::default()
+        };
+        assert!(certain.validate().is_err());
+
+        let overburn = GameRules {
+            ascend_failure_burn_bps: 10_001,
+            ..GameRules::default()
+        };
+        assert!(overburn.validate().is_err());
+        let half = GameRules {
+            ascend_failure_burn_bps: 5_000,
+            ..GameRules::default()
+        };
+        assert_eq!(
+            half.ascend_failure_burn(Uint128::new(501)),
+            Uint128::new(250)
+        );
+
+        let mut always_mutates = GameRules::default();
+        always_mutates.splice_mutations[0] = SpliceMutation {
+            rot: 200,
+            primordial: 57,
+        };
+        assert!(always_mutates.validate().is_err());
+    }
+
+    #[test]
+    fn substrate_tables() {
+        let rules = GameRules::default();
+        assert_eq!(rules.share_multiplier(0), 1);
+        assert_eq!(rules.share_multiplier(5), 8);
+        assert_eq!(rules.spin_cost_multiplier(4), 10);
+        assert_eq!(rules.spin_success_threshold(2), 128);
+        assert_eq!(rules.spin_success_threshold(3), 140);
+        // Past the last level
+        assert_eq!(rules.spin_cost_multiplier(9), 20);
+        assert_eq!(rules.gene_edit_multiplier(0), 1);
+        assert_eq!(rules.gene_edit_multiplier(4), 5);
+
+        assert_eq!(
+            rules.target_share(&TraitTarget::Stem),
+            Decimal::from_ratio(1u128, 3u128)
+        );
+        assert!(rules.ascend_succeeds(1_999, 0));
+        assert!(!rules.ascend_succeeds(2_000, 0));
+
+        assert_eq!(rules.splice_mutation(0, 1).rot, 11);
+        assert_eq!(rules.splice_mutation(4, 4).rot, 49);
+        assert_eq!(rules.splice_mutation(3, 4).rot, 39);
+    }
+
+    #[test]
+    fn ascend_pity() {
+        let rules = GameRules::default();
+        assert_eq!(rules.ascend_chance(0), 2_000);
+        assert_eq!(rules.ascend_chance(3), 5_000);
+        assert!(rules.ascend_succeeds(4_999, 3));
+        // The fifth attempt is certain
+        assert_eq!(rules.ascend_chance(4), BPS);
+        assert!(rules.ascend_succeeds(9_999, 4));
+
+        let no_guarantee = GameRules {
+            ascend_pity_bps: 3_000,
+            ascend_guaranteed_attempt: 0,
+            ..GameRules::default()
+        };
+        assert_eq!(no_guarantee.ascend_chance(1), 5_000);
+        assert_eq!(no_guarantee.ascend_chance(100), BPS);
+    }
+}
@@ -0,0 +1,138 @@
+use spore_fates::cw721::TraitExtension;
+
+use crate::msg::TraitTarget;
+use crate::rules::GameRules;
+use crate::splice::PRIMORDIAL;
+
+/// Highest and lowest value of a volatile trait
+const MAX_TRAIT: i8 = 3;
+const MIN_TRAIT: i8 = -3;
+
+/// Base stat at which a trait is Apex and can't lose value
+const APEX_BASE: u8 = 10;
+
+/// Outcome of a spin on one trait
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpinResult {
+    pub success: bool,
+    pub old_value: i8,
+    pub new_value: i8,
+}
+
+/// Current value and base stat of `target`
+pub fn trait_value(traits: &TraitExtension, target: &TraitTarget) -> (i8, u8) {
+    match target {
+        TraitTarget::Cap => (traits.cap, traits.base_cap),
+        TraitTarget::Stem => (traits.stem, traits.base_stem),
+        TraitTarget::Spores => (traits.spores, traits.base_spores),
+    }
+}
+
+pub fn set_trait_value(traits: &mut TraitExtension, target: &TraitTarget, value: i8) {
+    match target {
+        TraitTarget::Cap => traits.cap = value,
+        TraitTarget::Stem => traits.stem = value,
+        TraitTarget::Spores => traits.spores = value,
+    }
+}
+
+/// Spin `target` with the random byte `random_value`
+pub fn spin_result(
+    rules: &GameRules,
+    traits: &TraitExtension,
+    target: &TraitTarget,
+    random_value: u8,
+) -> SpinResult {
+    let (current_val, current_base) = trait_value(traits, target);
+
+    // B. Determine Win/Loss
+    let success_threshold = rules.spin_success_threshold(traits.substrate);
+    let is_success = random_value >= success_threshold;
+
+    let new_val = if is_success {
+        if traits.substrate >= 4 && random_value.is_multiple_of(10) {
+            (current_val + 2).min(MAX_TRAIT)
+        } else if current_val == -1 {
+            1
+        } else {
+            (current_val + 1).min(MAX_TRAIT)
+        }
+    } else {
+        // Protection Logic
+        let primordial_count = count_primordial(&traits.genes);
+        let has_stability = primordial_count >= 3;
+
+        // Protection Logic
+        if has_stability {
+            // Do nothing on failure (Stable)
+            current_val
+        }
+        // Existing protections (Base 10 or Substrate 2)
+        else if current_base >= APEX_BASE || (current_val == 1 && traits.substrate >= 2) {
+            current_val
+        } else if current_val == 1 {
+            -1
+        } else {
+            (current_val - 1).max(MIN_TRAIT)
+        }
+    };
+
+    SpinResult {
+        success: is_success,
+        old_value: current_val,
+        new_value: new_val,
+    }
+}
+
+fn count_primordial(genes: &[u8]) -> usize {
+    genes.iter().filter(|&&g| g == PRIMORDIAL).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protections() {
+        let rules = GameRules::default();
+        let traits = |cap: i8, substrate: u8| TraitExtension {
+            cap,
+            substrate,
+            genes: vec![0; 8],
+            ..TraitExtension::default()
+        };
+        let spin = |traits: &TraitExtension, byte: u8| {
+            spin_result(&rules, traits, &TraitTarget::Cap, byte).new_value
+        };
+
+        // -1 jumps straight to +1, +1 drops straight to -1
+        assert_eq!(spin(&traits(-1, 0), 200), 1);
+        assert_eq!(spin(&traits(1, 0), 0), -1);
+        // Level 2 keeps its +1
+        assert_eq!(spin(&traits(1, 2), 0), 1);
+        // Level 4 crits on multiples of 10
+        assert_eq!(spin(&traits(0, 4), 140), 2);
+        assert_eq!(spin(&traits(0, 4), 141), 1);
+        // 139 wins at level 2 but loses at level 3
+        assert!(spin_result(&rules, &traits(0, 2), &TraitTarget::Cap, 139).success);
+        assert!(!spin_result(&rules, &traits(0, 3), &TraitTarget::Cap, 139).success);
+
+        let apex = TraitExtension {
+            base_cap: APEX_BASE,
+            ..traits(-2, 0)
+        };
+        assert_eq!(spin(&apex, 0), -2);
+        let stable = TraitExtension {
+            genes: vec![4, 4, 4, 0, 0, 0, 0, 0],
+            ..traits(-2, 0)
+        };
+        assert_eq!(spin(&stable, 0), -2);
+        // Spores genes give no stability
+        let spores = TraitExtension {
+            genes: vec![3, 3, 3, 0, 0, 0, 0, 0],
+            ..traits(-2, 0)
+        };
+        assert_eq!(spin(&spores, 0), -3);
+        assert_eq!(spin(&traits(-3, 0), 0), MIN_TRAIT);
+    }
+}
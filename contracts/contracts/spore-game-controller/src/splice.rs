@@ -0,0 +1,323 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{Decimal, StdResult, Uint128, Uint256};
+use spore_fates::cw721::{TraitExtension, MAX_SUBSTRATE};
+
+use crate::msg::{GeneOdds, SplicePreviewResponse, StatTierOdds, SubstrateOdds};
+use crate::rules::{GameRules, SpliceMutation};
+
+/// Gene slots of a spliced child
+pub const GENE_SLOTS: usize = 8;
+
+/// Gene ids, Rot (0) through Primordial (4)
+pub const GENE_KINDS: usize = 5;
+const ROT: u8 = 0;
+pub const PRIMORDIAL: u8 = 4;
+
+/// Byte values a random byte can take
+const BYTE_OUTCOMES: u128 = 256;
+
+/// What a child's gene slot gets, from that slot's random byte
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SlotOutcome {
+    Primordial,
+    Rot,
+    Parent1,
+    Parent2,
+}
+
+impl SlotOutcome {
+    pub fn gene(self, parent_1: u8, parent_2: u8) -> u8 {
+        match self {
+            SlotOutcome::Primordial => PRIMORDIAL,
+            SlotOutcome::Rot => ROT,
+            SlotOutcome::Parent1 => parent_1,
+            SlotOutcome::Parent2 => parent_2,
+        }
+    }
+}
+
+pub fn slot_outcome(mutation: &SpliceMutation, rng_byte: u8) -> SlotOutcome {
+    let primordial_below = mutation.primordial as u16;
+    let rot_below = primordial_below + mutation.rot as u16;
+    if (rng_byte as u16) < primordial_below {
+        SlotOutcome::Primordial
+    } else if (rng_byte as u16) < rot_below {
+        SlotOutcome::Rot
+    } else if rng_byte.is_multiple_of(2) {
+        SlotOutcome::Parent1
+    } else {
+        SlotOutcome::Parent2
+    }
+}
+
+/// A fresh gene for an edited slot: the splice mutation odds, otherwise a Cap,
+/// Stem or Spores gene
+pub fn reroll_gene(mutation: &SpliceMutation, mutation_byte: u8, gene_byte: u8) -> u8 {
+    match slot_outcome(mutation, mutation_byte) {
+        SlotOutcome::Primordial => PRIMORDIAL,
+        SlotOutcome::Rot => ROT,
+        SlotOutcome::Parent1 | SlotOutcome::Parent2 => 1 + gene_byte % 3,
+    }
+}
+
+/// The average of the parents' substrates, plus a synergy bonus. Two level 4s
+/// are the only way to the Overmind.
+pub fn inherit_substrate(parent_1: u8, parent_2: u8, mutation_byte: u8) -> u8 {
+    let mut inherited = (parent_1 + parent_2) / 2;
+
+    // Synergy Bonus (Chance to upgrade if parents are equal)
+    if parent_1 > 0 && parent_1 == parent_2 && mutation_byte < 51 {
+        inherited += 1;
+    }
+
+    inherited.min(MAX_SUBSTRATE)
+}
+
+/// Exact odds of every splice outcome, found by running the resolver's rules over
+/// each value of the random bytes
+pub fn preview_splice(
+    rules: &GameRules,
+    parent_1: &TraitExtension,
+    parent_2: &TraitExtension,
+) -> StdResult<SplicePreviewResponse> {
+    let mutation = rules.splice_mutation(parent_1.substrate, parent_2.substrate);
+
+    // How many byte values give each outcome
+    let mut outcomes: BTreeMap<SlotOutcome, u128> = BTreeMap::new();
+    for byte in 0..=u8::MAX {
+        *outcomes.entry(slot_outcome(mutation, byte)).or_default() += 1;
+    }
+
+    // Gene counts (cap, stem, spores) over all slots so far, with their weight
+    let mut counts: BTreeMap<(u8, u8, u8), u128> = BTreeMap::from([((0, 0, 0), 1)]);
+    let mut slots = Vec::with_capacity(GENE_SLOTS);
+    for i in 0..GENE_SLOTS {
+        let gene_1 = parent_1.genes.get(i).cloned().unwrap_or(0);
+        let gene_2 = parent_2.genes.get(i).cloned().unwrap_or(0);
+
+        let mut genes = [0u128; GENE_KINDS];
+        for (outcome, weight) in &outcomes {
+            let gene = outcome.gene(gene_1, gene_2) as usize;
+            // Unknown ids count for nothing, like Rot
+            genes[if gene < GENE_KINDS { gene } else { 0 }] += weight;
+        }
+        let chance = |gene: usize| Decimal::from_ratio(genes[gene], BYTE_OUTCOMES);
+        slots.push(GeneOdds {
+            rot: chance(0),
+            cap: chance(1),
+            stem: chance(2),
+            spores: chance(3),
+            primordial: chance(4),
+        });
+
+        let mut next: BTreeMap<(u8, u8, u8), u128> = BTreeMap::new();
+        for (&(cap, stem, spores), &weight) in &counts {
+            for (gene, &gene_weight) in genes.iter().enumerate() {
+                if gene_weight == 0 {
+                    continue;
+                }
+                let key = match gene {
+                    1 => (cap + 1, stem, spores),
+                    2 => (cap, stem + 1, spores),
+                    3 => (cap, stem, spores + 1),
+                    4 => (cap + 1, stem + 1, spores + 1),
+                    _ => (cap, stem, spores),
+                };
+                *next.entry(key).or_default() += weight * gene_weight;
+            }
+        }
+        counts = next;
+    }
+    let gene_outcomes = BYTE_OUTCOMES.pow(GENE_SLOTS as u32);
+
+    let mut substrates: BTreeMap<u8, u128> = BTreeMap::new();
+    for byte in 0..=u8::MAX {
+        let substrate = inherit_substrate(parent_1.substrate, parent_2.substrate, byte);
+        *substrates.entry(substrate).or_default() += 1;
+    }
+
+    let mut base_cap: BTreeMap<u8, u128> = BTreeMap::new();
+    let mut base_stem: BTreeMap<u8, u128> = BTreeMap::new();
+    let mut base_spores: BTreeMap<u8, u128> = BTreeMap::new();
+    let mut total_shares = Uint256::zero();
+    for (&(cap, stem, spores), &weight) in &counts {
+        // Any genes with these counts give the same base stats
+        let mut child = TraitExtension {
+            genes: [(1, cap), (2, stem), (3, spores)]
+                .into_iter()
+                .flat_map(|(gene, count)| std::iter::repeat_n(gene, count as usize))
+                .collect(),
+            ..TraitExtension::default()
+        };
+        child.recalculate_base_stats();
+        *base_cap.entry(child.base_cap).or_default() += weight;
+        *base_stem.entry(child.base_stem).or_default() += weight;
+        *base_spores.entry(child.base_spores).or_default() += weight;
+
+        for (&substrate, &substrate_weight) in &substrates {
+            child.substrate = substrate;
+            let shares = crate::calculate_shares(rules, &child);
+            total_shares +=
+                Uint256::from(shares) * Uint256::from(weight) * Uint256::from(substrate_weight);
+        }
+    }
+
+    let tiers = |weights: BTreeMap<u8, u128>| -> Vec<StatTierOdds> {
+        weights
+            .into_iter()
+            .map(|(bonus, weight)| StatTierOdds {
+                bonus,
+                chance: Decimal::from_ratio(weight, gene_outcomes),
+            })
+            .collect()
+    };
+    let expected_shares =
+        total_shares / (Uint256::from(gene_outcomes) * Uint256::from(BYTE_OUTCOMES));
+
+    Ok(SplicePreviewResponse {
+        slots,
+        base_cap: tiers(base_cap),
+        base_stem: tiers(base_stem),
+        base_spores: tiers(base_spores),
+        substrate: substrates
+            .into_iter()
+            .map(|(substrate, weight)| SubstrateOdds {
+                substrate,
+                chance: Decimal::from_ratio(weight, BYTE_OUTCOMES),
+            })
+            .collect(),
+        expected_shares: Uint128::try_from(expected_shares)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parent(genes: Vec<u8>, substrate: u8) -> TraitExtension {
+        let mut traits = TraitExtension {
+            substrate,
+            genes,
+            ..TraitExtension::default()
+        };
+        traits.recalculate_base_stats();
+        traits
+    }
+
+    #[test]
+    fn substrate_inheritance() {
+        // Synergy needs equal parents and a low mutation byte
+        assert_eq!(inherit_substrate(4, 4, 50), MAX_SUBSTRATE);
+        assert_eq!(inherit_substrate(4, 4, 51), 4);
+        assert_eq!(inherit_substrate(3, 4, 0), 3);
+        assert_eq!(inherit_substrate(0, 0, 0), 0);
+        // Two Overminds can't go higher
+        assert_eq!(inherit_substrate(5, 5, 0), MAX_SUBSTRATE);
+    }
+
+    #[test]
+    fn slot_outcomes() {
+        let mutation = SpliceMutation {
+            rot: 11,
+            primordial: 2,
+        };
+        assert_eq!(slot_outcome(&mutation, 1), SlotOutcome::Primordial);
+        assert_eq!(slot_outcome(&mutation, 2), SlotOutcome::Rot);
+        assert_eq!(slot_outcome(&mutation, 12), SlotOutcome::Rot);
+        assert_eq!(slot_outcome(&mutation, 13), SlotOutcome::Parent2);
+        assert_eq!(slot_outcome(&mutation, 14), SlotOutcome::Parent1);
+    }
+
+    #[test]
+    fn rerolled_genes() {
+        let mutation = SpliceMutation {
+            rot: 11,
+            primordial: 2,
+        };
+        assert_eq!(reroll_gene(&mutation, 0, 0), PRIMORDIAL);
+        assert_eq!(reroll_gene(&mutation, 5, 0), ROT);
+        // No mutation: never Rot
+        assert_eq!(reroll_gene(&mutation, 13, 0), 1);
+        assert_eq!(reroll_gene(&mutation, 200, 1), 2);
+        assert_eq!(reroll_gene(&mutation, 255, 5), 3);
+    }
+
+    #[test]
+    fn preview_odds_add_up() {
+        let rules = GameRules::default();
+        let cap = parent(vec![1; 8], 4);
+        let stem = parent(vec![2; 8], 4);
+        let preview = preview_splice(&rules, &cap, &stem).unwrap();
+
+        // Level 4 parents: 2 Primordial and 49 Rot bytes, 102 even and 103 odd left
+        assert_eq!(preview.slots.len(), GENE_SLOTS);
+        assert_eq!(
+            preview.slots[0],
+            GeneOdds {
+                rot: Decimal::from_ratio(49u128, 256u128),
+                cap: Decimal::from_ratio(102u128, 256u128),
+                stem: Decimal::from_ratio(103u128, 256u128),
+                spores: Decimal::zero(),
+                primordial: Decimal::from_ratio(2u128, 256u128),
+            }
+        );
+
+        // 51 of 256 mutation bytes make an Overmind
+        assert_eq!(
+            preview.substrate,
+            vec![
+                SubstrateOdds {
+                    substrate: 4,
+                    chance: Decimal::from_ratio(205u128, 256u128),
+                },
+                SubstrateOdds {
+                    substrate: 5,
+                    chance: Decimal::from_ratio(51u128, 256u128),
+                },
+            ]
+        );
+
+        // No spore genes unless a Primordial turns up
+        assert_eq!(preview.base_spores[0].bonus, 0);
+        let spores_total: Decimal = preview.base_spores.iter().map(|t| t.chance).sum();
+        let cap_total: Decimal = preview.base_cap.iter().map(|t| t.chance).sum();
+        assert!(Decimal::one() - spores_total < Decimal::permille(1));
+        assert!(Decimal::one() - cap_total < Decimal::permille(1));
+        assert!(!preview.expected_shares.is_zero());
+    }
+
+    #[test]
+    fn preview_of_identical_parents_without_mutation() {
+        let rules = GameRules {
+            splice_mutations: vec![
+                SpliceMutation {
+                    rot: 0,
+                    primordial: 0,
+                };
+                6
+            ],
+            ..GameRules::default()
+        };
+        // Level 0 parents never get the synergy bonus
+        let cap = parent(vec![1; 8], 0);
+        let preview = preview_splice(&rules, &cap, &cap).unwrap();
+
+        assert_eq!(preview.slots[3].cap, Decimal::one());
+        assert_eq!(
+            preview.base_cap,
+            vec![StatTierOdds {
+                bonus: 10,
+                chance: Decimal::one(),
+            }]
+        );
+        assert_eq!(preview.base_stem[0].bonus, 0);
+        assert_eq!(preview.base_stem[0].chance, Decimal::one());
+        assert_eq!(preview.substrate.len(), 1);
+        // Always exactly the parent
+        assert_eq!(
+            preview.expected_shares,
+            crate::calculate_shares(&rules, &cap)
+        );
+    }
+}
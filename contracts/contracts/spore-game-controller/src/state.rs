@@ -1,32 +1,71 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Decimal, Decimal256, Empty, Timestamp, Uint128};
+use cw20::Denom;
 use cw_storage_plus::{Item, Map};
 use spore_fates::game::GlobalBiomass;
 
+use crate::emissions::Emissions;
+use crate::fees::FeeSplit;
 use crate::msg::TraitTarget;
+use crate::pricing::PricingCurve;
+use crate::rules::GameRules;
+use crate::twap::BiomassTwap;
 
 #[cw_serde]
 pub struct GameConfig {
-    pub payment_denom: String,
+    /// May withdraw the treasury
+    pub admin: Addr,
+    pub payment: Denom,
     pub spin_cost: Uint128,
-    pub mint_cost: Uint128,
-    pub mint_cost_increment: Uint128,
+    pub mint_pricing: PricingCurve,
     pub cw721_addr: Addr,
     pub oracle_addr: Addr,
+    /// Rounds between the latest public drand round and a request's target round
+    pub min_round_delay: u64,
+    /// Seconds a request's block time may lag behind real time
+    pub max_block_time: u64,
+    pub fee_split: FeeSplit,
+    /// Receives the burn share of each payment
+    pub burn_addr: Option<Addr>,
+    /// Seconds between Sun Line snapshots
+    #[serde(default = "default_epoch_length")]
+    pub epoch_length: u64,
+    /// Epochs of ecosystem history kept for charts
+    #[serde(default = "default_history_retention")]
+    pub history_retention: u64,
+    #[serde(default)]
+    pub rules: GameRules,
+}
+
+fn default_epoch_length() -> u64 {
+    crate::DEFAULT_EPOCH_LENGTH
+}
+
+fn default_history_retention() -> u64 {
+    crate::DEFAULT_HISTORY_RETENTION
 }
 
 #[cw_serde]
 pub struct GlobalState {
     pub total_shares: Uint128,
-    pub global_reward_index: Uint128,
+    /// Rewards per share distributed so far
+    pub global_reward_index: Decimal256,
     pub spin_nonce: u64,
+    /// Distributed rewards the index couldn't represent yet, or that arrived
+    /// while nothing was staked; added to the next distribution
+    #[serde(default)]
+    pub undistributed_dust: Uint128,
 }
 
 #[cw_serde]
 pub struct TokenInfo {
     pub current_shares: Uint128,
+    /// `current_shares * global_reward_index` rounded down, at the last settlement
     pub reward_debt: Uint128,
     pub pending_rewards: Uint128,
+    /// Failed ascensions since the last success
+    #[serde(default)]
+    pub ascension_attempts: u32,
 }
 
 #[cw_serde]
@@ -42,6 +81,8 @@ pub struct GameStats {
     pub total_harvests: u64,
     pub total_splices: u64,
     pub total_ascensions: u64,
+    #[serde(default)]
+    pub total_gene_edits: u64,
 }
 
 #[cw_serde]
@@ -51,6 +92,9 @@ pub struct PendingSpin {
     pub target: TraitTarget,
     pub bid_amount: Uint128,
     pub target_round: u64,
+    /// Zero on requests made before this was recorded
+    #[serde(default)]
+    pub requested_at: Timestamp,
 }
 
 #[cw_serde]
@@ -59,6 +103,20 @@ pub struct LeaderboardEntry {
     pub score: Uint128,
 }
 
+/// Protocol fees taken from resolved mints and spins, less compost refunds
+#[cw_serde]
+#[derive(Default)]
+pub struct Treasury {
+    /// Held by the contract until the admin withdraws it
+    pub balance: Uint128,
+    pub total_accrued: Uint128,
+    pub total_withdrawn: Uint128,
+    pub total_burned: Uint128,
+    /// Paid back to players as compost refunds on recycle
+    #[serde(default)]
+    pub total_composted: Uint128,
+}
+
 pub const GAME_STATS: Item<GameStats> = Item::new("game_stats");
 pub const CONFIG: Item<GameConfig> = Item::new("config");
 pub const GLOBAL_STATE: Item<GlobalState> = Item::new("global_state");
@@ -66,7 +124,43 @@ pub const TOKEN_INFO: Map<&str, TokenInfo> = Map::new("token_info");
 pub const MINT_COUNTER: Item<u64> = Item::new("mint_counter");
 pub const PENDING_SPINS: Map<&str, PendingSpin> = Map::new("pending_spins");
 pub const BIOMASS: Item<GlobalBiomass> = Item::new("biomass");
+pub const BIOMASS_TWAP: Item<BiomassTwap> = Item::new("biomass_twap");
 pub const LEADERBOARD: Item<Vec<LeaderboardEntry>> = Item::new("leaderboard");
+pub const TREASURY: Item<Treasury> = Item::new("treasury");
+pub const EMISSIONS: Item<Emissions> = Item::new("emissions");
+
+/// The Sun Line holds for a whole epoch and is re-snapshotted at each boundary
+#[cw_serde]
+pub struct CanopyEpoch {
+    pub epoch: u64,
+    pub started_at: Timestamp,
+    /// Average canopy score of all active mushrooms when the epoch started
+    pub sun_line: Decimal,
+    pub active_mushrooms: u64,
+}
+
+pub const CANOPY_EPOCH: Item<CanopyEpoch> = Item::new("canopy_epoch");
+
+/// Ecosystem state at the start of a canopy epoch
+#[cw_serde]
+pub struct EcosystemSnapshot {
+    pub epoch: u64,
+    pub time: Timestamp,
+    pub biomass: GlobalBiomass,
+    pub cap_multiplier: Decimal,
+    pub stem_multiplier: Decimal,
+    pub spores_multiplier: Decimal,
+    pub sun_line: Decimal,
+    pub total_shares: Uint128,
+    pub active_supply: u64,
+    pub mint_price: Uint128,
+    /// Cumulative; consecutive snapshots give the volume per epoch
+    pub total_mint_volume: Uint128,
+    pub total_spin_volume: Uint128,
+}
+
+// epoch -> snapshot
+pub const ECOSYSTEM_HISTORY: Map<u64, EcosystemSnapshot> = Map::new("ecosystem_history");
 
 // Token locking to prevent concurrent operations
 pub const LOCKED_TOKENS: Map<&str, String> = Map::new("locked_tokens"); // token_id -> reason
@@ -77,6 +171,8 @@ pub struct PendingMint {
     pub payment_amount: Uint128,
     pub target_round: u64,
     pub mint_id: String,
+    #[serde(default)]
+    pub requested_at: Timestamp,
 }
 
 pub const PENDING_MINTS: Map<&str, PendingMint> = Map::new("pending_mints");
@@ -88,6 +184,8 @@ pub struct PendingSplice {
     pub parent_2_id: String,
     pub target_round: u64,
     pub splice_id: String,
+    #[serde(default)]
+    pub requested_at: Timestamp,
 }
 
 pub const PENDING_SPLICES: Map<&str, PendingSplice> = Map::new("pending_splices");
@@ -98,10 +196,25 @@ pub struct PendingAscend {
     pub token_id: String,
     pub target_round: u64,
     pub burned_amount: Uint128,
+    #[serde(default)]
+    pub requested_at: Timestamp,
 }
 
 pub const PENDING_ASCENDS: Map<&str, PendingAscend> = Map::new("pending_ascends");
 
+#[cw_serde]
+pub struct PendingGeneEdit {
+    pub player: Addr,
+    pub token_id: String,
+    pub slot: u8,
+    pub bid_amount: Uint128,
+    pub target_round: u64,
+    #[serde(default)]
+    pub requested_at: Timestamp,
+}
+
+pub const PENDING_GENE_EDITS: Map<&str, PendingGeneEdit> = Map::new("pending_gene_edits");
+
 // Per-player index: tracks which tokens each player owns.
 // Eliminates the need for cross-contract CW721 Tokens queries in profile lookups.
 #[cw_serde]
@@ -110,3 +223,31 @@ pub struct PlayerInfo {
 }
 
 pub const PLAYER_INFO: Map<&str, PlayerInfo> = Map::new("player_info");
+
+/// Ancestry of a mushroom, kept as a tombstone once it's burned
+#[cw_serde]
+pub struct LineageRecord {
+    /// Empty for minted mushrooms
+    pub parents: Vec<String>,
+    pub generation: u32,
+    /// Drand round its mint or splice resolved on; 0 if born before lineage tracking
+    pub birth_round: u64,
+    pub burned: bool,
+}
+
+pub const LINEAGE: Map<&str, LineageRecord> = Map::new("lineage");
+/// (parent, child) for every splice
+pub const CHILDREN: Map<(&str, &str), Empty> = Map::new("children");
+
+// First-release config layout, converted by `migrate`
+#[cw_serde]
+pub struct LegacyGameConfig {
+    pub payment_denom: String,
+    pub spin_cost: Uint128,
+    pub mint_cost: Uint128,
+    pub mint_cost_increment: Uint128,
+    pub cw721_addr: Addr,
+    pub oracle_addr: Addr,
+}
+
+pub const LEGACY_CONFIG: Item<LegacyGameConfig> = Item::new("config");
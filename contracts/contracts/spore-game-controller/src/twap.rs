@@ -0,0 +1,131 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{StdResult, Timestamp, Uint128, Uint256};
+use spore_fates::game::GlobalBiomass;
+
+/// Time integral of the biomass split (mass x seconds) up to `time`
+#[cw_serde]
+pub struct BiomassCumulative {
+    pub cap: Uint256,
+    pub stem: Uint256,
+    pub spores: Uint256,
+    pub time: Timestamp,
+}
+
+impl BiomassCumulative {
+    fn zero(time: Timestamp) -> Self {
+        BiomassCumulative {
+            cap: Uint256::zero(),
+            stem: Uint256::zero(),
+            spores: Uint256::zero(),
+            time,
+        }
+    }
+
+    /// Extend to `now`, assuming `biomass` held since `self.time`
+    fn project(&self, biomass: &GlobalBiomass, now: Timestamp) -> Self {
+        let elapsed = Uint256::from(now.seconds().saturating_sub(self.time.seconds()));
+        BiomassCumulative {
+            cap: self.cap + Uint256::from(biomass.total_base_cap) * elapsed,
+            stem: self.stem + Uint256::from(biomass.total_base_stem) * elapsed,
+            spores: self.spores + Uint256::from(biomass.total_base_spores) * elapsed,
+            time: now.max(self.time),
+        }
+    }
+}
+
+/// Time-weighted biomass, so a harvest can't be timed against a mint or recycle
+/// made just before it
+#[cw_serde]
+pub struct BiomassTwap {
+    /// As of the last biomass change
+    pub cumulative: BiomassCumulative,
+    /// At the start of the current canopy epoch
+    pub epoch_start: BiomassCumulative,
+    /// At the start of the previous canopy epoch; averages run from here
+    pub window_start: BiomassCumulative,
+}
+
+impl BiomassTwap {
+    pub fn new(now: Timestamp) -> Self {
+        BiomassTwap {
+            cumulative: BiomassCumulative::zero(now),
+            epoch_start: BiomassCumulative::zero(now),
+            window_start: BiomassCumulative::zero(now),
+        }
+    }
+
+    /// Accumulate `biomass` (the split being replaced) up to `now`
+    pub fn record(&mut self, biomass: &GlobalBiomass, now: Timestamp) {
+        self.cumulative = self.cumulative.project(biomass, now);
+    }
+
+    /// Move the window along at an epoch boundary. Nothing changes the biomass
+    /// between the boundary and the rollover, so the accumulator is exact there.
+    pub fn roll_epoch(&mut self, biomass: &GlobalBiomass, started_at: Timestamp) {
+        self.window_start = self.epoch_start.clone();
+        self.epoch_start = self.cumulative.project(biomass, started_at);
+    }
+
+    /// Average split since the window start; the spot split until time has passed
+    pub fn average(&self, biomass: &GlobalBiomass, now: Timestamp) -> StdResult<GlobalBiomass> {
+        let span = now
+            .seconds()
+            .saturating_sub(self.window_start.time.seconds());
+        if span == 0 {
+            return Ok(biomass.clone());
+        }
+        let current = self.cumulative.project(biomass, now);
+        let span = Uint256::from(span);
+        let mean = |total: Uint256, start: Uint256| -> StdResult<u128> {
+            Ok(Uint128::try_from((total - start) / span)?.u128())
+        };
+        Ok(GlobalBiomass {
+            total_base_cap: mean(current.cap, self.window_start.cap)?,
+            total_base_stem: mean(current.stem, self.window_start.stem)?,
+            total_base_spores: mean(current.spores, self.window_start.spores)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: u64) -> Timestamp {
+        Timestamp::from_seconds(seconds)
+    }
+
+    fn biomass(cap: u128, stem: u128, spores: u128) -> GlobalBiomass {
+        GlobalBiomass {
+            total_base_cap: cap,
+            total_base_stem: stem,
+            total_base_spores: spores,
+        }
+    }
+
+    #[test]
+    fn averages_over_time() {
+        let mut twap = BiomassTwap::new(at(0));
+        // 100 caps for 90s, then a whale adds 900 for the last 10s
+        twap.record(&biomass(100, 0, 0), at(90));
+        let spot = biomass(1_000, 0, 0);
+        assert_eq!(twap.average(&spot, at(100)).unwrap(), biomass(190, 0, 0));
+        // No time has passed yet
+        assert_eq!(BiomassTwap::new(at(5)).average(&spot, at(5)).unwrap(), spot);
+    }
+
+    #[test]
+    fn window_spans_previous_epoch() {
+        let mut twap = BiomassTwap::new(at(0));
+        twap.record(&biomass(0, 0, 0), at(50));
+        // Epochs start at 100 and 200; the split was (0, 10, 0) since 50
+        let split = biomass(0, 10, 0);
+        twap.roll_epoch(&split, at(100));
+        twap.roll_epoch(&split, at(200));
+        assert_eq!(twap.window_start.time, at(100));
+        assert_eq!(twap.window_start.stem, Uint256::from(500u128));
+
+        // Only [100, 250) counts: 10 stem throughout
+        assert_eq!(twap.average(&split, at(250)).unwrap(), split);
+    }
+}
@@ -161,9 +161,8 @@ echo ""
 # ------------------------------------------------------------------------------
 
 echo "--- 3. Instantiating Drand Oracle ---"
-# Oracle usually takes empty init msg or owner config depending on implementation.
-# Assuming empty per standard cw-drand-oracle.
-INIT_ORACLE='{}'
+# Paid subscription deliveries are priced at the chain's minimum gas price.
+INIT_ORACLE='{"fee_denom":"inj","base_fee":"0","gas_price":"160000000"}'
 
 ORACLE_ADDRESS=$(instantiate_contract "$ORACLE_CODE_ID" "$INIT_ORACLE" "spore-oracle" "$DEPLOYER_ADDRESS")
 echo "✅ Oracle Address: $ORACLE_ADDRESS"
@@ -182,10 +181,9 @@ INIT_GAME=$(jq -n \
   --arg oracle "$ORACLE_ADDRESS" \
   --arg cw721 "$CW721_ADDRESS" \
   '{
-    payment_denom: $payment,
+    payment: { native: $payment },
     spin_cost: $cost,
-    mint_cost: $mint_cost,
-    mint_cost_increment: $slope,
+    mint_pricing: { linear: { base: $mint_cost, increment: $slope } },
     oracle_addr: $oracle,
     cw721_addr: $cw721
   }')
@@ -5,6 +5,9 @@ use cw721::msg::NftExtensionMsg;
 use cw721::state::Trait;
 use cw721::Action;
 
+/// Highest substrate level, the Overmind
+pub const MAX_SUBSTRATE: u8 = 5;
+
 #[cw_serde]
 #[derive(Default)]
 pub struct TraitExtension {
@@ -19,6 +22,10 @@ pub struct TraitExtension {
     pub base_cap: u8,
     pub base_stem: u8,
     pub base_spores: u8,
+
+    /// Splices since the founding mint (0 for minted mushrooms)
+    #[serde(default)]
+    pub generation: u32,
 }
 
 // You will also need a helper to calculate Base Stats from Genes
@@ -332,6 +339,11 @@ impl From<TraitExtension> for Vec<Trait> {
                 trait_type: "genome".to_string(),
                 value: gene_string,
             },
+            Trait {
+                display_type: None,
+                trait_type: "generation".to_string(),
+                value: t.generation.to_string(),
+            },
         ]
     }
 }
@@ -1,3 +1,4 @@
 pub mod cw721;
 pub mod game;
-pub mod tests;
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use thiserror::Error;
+
+/// Chain hash of the drand quicknet network (bls-unchained-g1-rfc9380)
+pub const QUICKNET_CHAIN_HASH: &str =
+    "52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971";
+
+/// A beacon as returned by the drand HTTP API (`/{chain_hash}/public/{round}`)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DrandBeacon {
+    pub round: u64,
+    /// Hex encoded sha256(signature)
+    pub randomness: String,
+    /// Hex encoded BLS signature over the round number
+    pub signature: String,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum FetchError {
+    #[error("round {0} is not available yet")]
+    NotFound(u64),
+
+    #[error("http error: {0}")]
+    Http(String),
+
+    #[error("malformed beacon: {0}")]
+    Decode(String),
+}
+
+/// Anything that can hand out drand beacons
+pub trait BeaconSource {
+    fn latest(&self) -> Result<DrandBeacon, FetchError>;
+    fn round(&self, round: u64) -> Result<DrandBeacon, FetchError>;
+}
+
+/// Fetches beacons from a drand HTTP API endpoint, e.g. `https://api.drand.sh`
+pub struct HttpSource {
+    base_url: String,
+    chain_hash: String,
+    agent: ureq::Agent,
+}
+
+impl HttpSource {
+    pub fn new(base_url: &str, chain_hash: &str, timeout: Duration) -> Self {
+        HttpSource {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            chain_hash: chain_hash.to_string(),
+            agent: ureq::AgentBuilder::new().timeout(timeout).build(),
+        }
+    }
+
+    fn get(&self, path: &str, round: Option<u64>) -> Result<DrandBeacon, FetchError> {
+        let url = format!("{}/{}/public/{}", self.base_url, self.chain_hash, path);
+
+        match self.agent.get(&url).call() {
+            Ok(response) => response
+                .into_json::<DrandBeacon>()
+                .map_err(|e| FetchError::Decode(e.to_string())),
+            Err(ureq::Error::Status(404, _)) | Err(ureq::Error::Status(425, _)) => {
+                Err(FetchError::NotFound(round.unwrap_or_default()))
+            }
+            Err(e) => Err(FetchError::Http(e.to_string())),
+        }
+    }
+}
+
+impl BeaconSource for HttpSource {
+    fn latest(&self) -> Result<DrandBeacon, FetchError> {
+        self.get("latest", None)
+    }
+
+    fn round(&self, round: u64) -> Result<DrandBeacon, FetchError> {
+        let beacon = self.get(&round.to_string(), Some(round))?;
+        if beacon.round != round {
+            return Err(FetchError::Decode(format!(
+                "asked for round {} but got {}",
+                round, beacon.round
+            )));
+        }
+        Ok(beacon)
+    }
+}
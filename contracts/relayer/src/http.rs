@@ -0,0 +1,52 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+/// Minimal blocking HTTP/1.1 server for GET requests. Each connection is answered
+/// with `handler(path) -> (status, body)` and closed. Used for the metrics endpoint
+/// and the offline drand stand-in; not meant to face the internet.
+pub fn serve<F>(listener: TcpListener, handler: F) -> thread::JoinHandle<()>
+where
+    F: Fn(&str) -> (u16, String) + Send + 'static,
+{
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // A broken client connection must not take the server down
+            let _ = respond(stream, &handler);
+        }
+    })
+}
+
+fn respond<F>(mut stream: TcpStream, handler: &F) -> std::io::Result<()>
+where
+    F: Fn(&str) -> (u16, String),
+{
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // Drain the headers
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let (status, body) = handler(path);
+    let reason = match status {
+        200 => "OK",
+        404 => "Not Found",
+        _ => "Error",
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
@@ -0,0 +1,6 @@
+pub mod drand;
+pub mod http;
+pub mod metrics;
+pub mod relayer;
+pub mod standin;
+pub mod submitter;
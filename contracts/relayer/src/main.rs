@@ -0,0 +1,88 @@
+use std::env;
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::Duration;
+
+use drand_relayer::drand::{HttpSource, QUICKNET_CHAIN_HASH};
+use drand_relayer::http;
+use drand_relayer::metrics::Metrics;
+use drand_relayer::relayer::{verifier_for, Backoff, Relayer};
+use drand_relayer::submitter::{CliSubmitter, StdoutSubmitter, Submitter};
+
+/// Configuration is read from the environment:
+///
+/// - `DRAND_URL`: drand HTTP API endpoint (default `https://api.drand.sh`)
+/// - `DRAND_CHAIN_HASH`: network to follow (default quicknet, the only one the oracle verifies)
+/// - `START_ROUND`: first round to relay (default: the current tip)
+/// - `POLL_SECS`: delay between polls (default 3, the quicknet period)
+/// - `MAX_BATCH`: rounds relayed per tick while catching up (default 20)
+/// - `METRICS_ADDR`: where to serve Prometheus metrics, e.g. `0.0.0.0:9100` (optional)
+/// - `ORACLE_ADDRESS`: oracle contract; without it messages are printed (dry run)
+/// - `CHAIN_BINARY`: CLI used to broadcast (default `injectived`)
+/// - `TX_FLAGS`: extra flags for the CLI, e.g. `--from relayer --chain-id injective-888 --fees 500000000000000inj --gas 2000000`
+/// - `TX_TIMEOUT_SECS`: how long to wait for each transaction to be included (default 60)
+fn main() {
+    let url = env_or("DRAND_URL", "https://api.drand.sh");
+    let chain_hash = env_or("DRAND_CHAIN_HASH", QUICKNET_CHAIN_HASH);
+    let verifier = verifier_for(&chain_hash).unwrap_or_else(|| {
+        panic!(
+            "DRAND_CHAIN_HASH {} is not supported, only quicknet ({}) beacons can be verified",
+            chain_hash, QUICKNET_CHAIN_HASH
+        )
+    });
+    let poll = Duration::from_secs(env_parse("POLL_SECS", 3));
+    let max_batch = env_parse("MAX_BATCH", 20);
+
+    let metrics = Arc::new(Metrics::default());
+    if let Ok(addr) = env::var("METRICS_ADDR") {
+        let listener = TcpListener::bind(&addr).expect("failed to bind METRICS_ADDR");
+        let metrics = metrics.clone();
+        http::serve(listener, move |path| match path {
+            "/metrics" => (200, metrics.render()),
+            _ => (404, String::new()),
+        });
+        eprintln!("serving metrics on http://{}/metrics", addr);
+    }
+
+    let source = HttpSource::new(&url, &chain_hash, Duration::from_secs(10));
+
+    let submitter: Box<dyn Submitter> = match env::var("ORACLE_ADDRESS") {
+        Ok(oracle) => Box::new(CliSubmitter {
+            binary: env_or("CHAIN_BINARY", "injectived"),
+            oracle,
+            flags: env_or("TX_FLAGS", "")
+                .split_whitespace()
+                .map(String::from)
+                .collect(),
+            poll: Duration::from_secs(1),
+            timeout: Duration::from_secs(env_parse("TX_TIMEOUT_SECS", 60)),
+        }),
+        Err(_) => {
+            eprintln!("ORACLE_ADDRESS not set, printing messages instead of broadcasting");
+            Box::new(StdoutSubmitter)
+        }
+    };
+
+    let mut relayer = Relayer::new(source, submitter, metrics)
+        .with_verifier(verifier)
+        .with_max_batch(max_batch);
+    if let Ok(round) = env::var("START_ROUND") {
+        relayer = relayer.starting_at(round.parse().expect("START_ROUND must be a number"));
+    }
+
+    let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(60));
+    relayer.run(poll, &mut backoff)
+}
+
+fn env_or(key: &str, default: &str) -> String {
+    env::var(key).unwrap_or_else(|_| default.to_string())
+}
+
+fn env_parse(key: &str, default: u64) -> u64 {
+    env::var(key)
+        .map(|v| {
+            v.parse()
+                .unwrap_or_else(|_| panic!("{} must be a number", key))
+        })
+        .unwrap_or(default)
+}
@@ -0,0 +1,56 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Relayer counters, rendered in the Prometheus text format
+#[derive(Default)]
+pub struct Metrics {
+    pub beacons_fetched: AtomicU64,
+    pub beacons_submitted: AtomicU64,
+    pub gaps_filled: AtomicU64,
+    pub fetch_errors: AtomicU64,
+    pub verification_failures: AtomicU64,
+    pub submit_errors: AtomicU64,
+    pub last_submitted_round: AtomicU64,
+}
+
+impl Metrics {
+    pub fn inc(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn add(counter: &AtomicU64, value: u64) {
+        counter.fetch_add(value, Ordering::Relaxed);
+    }
+
+    pub fn get(counter: &AtomicU64) -> u64 {
+        counter.load(Ordering::Relaxed)
+    }
+
+    pub fn render(&self) -> String {
+        let rows = [
+            ("beacons_fetched_total", "counter", &self.beacons_fetched),
+            (
+                "beacons_submitted_total",
+                "counter",
+                &self.beacons_submitted,
+            ),
+            ("gaps_filled_total", "counter", &self.gaps_filled),
+            ("fetch_errors_total", "counter", &self.fetch_errors),
+            (
+                "verification_failures_total",
+                "counter",
+                &self.verification_failures,
+            ),
+            ("submit_errors_total", "counter", &self.submit_errors),
+            ("last_submitted_round", "gauge", &self.last_submitted_round),
+        ];
+
+        rows.iter()
+            .map(|(name, kind, value)| {
+                format!(
+                    "# TYPE drand_relayer_{name} {kind}\ndrand_relayer_{name} {}\n",
+                    Self::get(value)
+                )
+            })
+            .collect()
+    }
+}
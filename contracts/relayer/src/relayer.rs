@@ -0,0 +1,473 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use cosmwasm_std::{HexBinary, Uint64};
+use drand_oracle::contract::verify::verify_quicknet_beacon;
+use drand_oracle::msg::ExecuteMsg;
+use thiserror::Error;
+
+use crate::drand::{BeaconSource, DrandBeacon, FetchError, QUICKNET_CHAIN_HASH};
+use crate::metrics::Metrics;
+use crate::submitter::{SubmitError, Submitter};
+
+/// Verifies a beacon signature and returns the derived randomness
+pub type Verifier = fn(u64, &[u8]) -> Result<[u8; 32], String>;
+
+/// Signature check for the network with `chain_hash`. The oracle only accepts
+/// quicknet beacons, so that is the only network with one.
+pub fn verifier_for(chain_hash: &str) -> Option<Verifier> {
+    match chain_hash {
+        QUICKNET_CHAIN_HASH => Some(verify_quicknet_beacon),
+        _ => None,
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum RelayError {
+    #[error(transparent)]
+    Fetch(#[from] FetchError),
+
+    #[error(transparent)]
+    Submit(#[from] SubmitError),
+
+    #[error("round {round} failed verification: {msg}")]
+    Verification { round: u64, msg: String },
+}
+
+/// Exponential backoff between failed ticks, reset after a successful one
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    current: Option<Duration>,
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Backoff {
+            initial,
+            max,
+            current: None,
+        }
+    }
+
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = match self.current {
+            None => self.initial,
+            Some(current) => (current * 2).min(self.max),
+        };
+        self.current = Some(delay);
+        delay
+    }
+
+    pub fn reset(&mut self) {
+        self.current = None;
+    }
+}
+
+pub struct Relayer<S: BeaconSource, T: Submitter> {
+    source: S,
+    submitter: T,
+    metrics: Arc<Metrics>,
+    verifier: Verifier,
+    /// Next round to relay; `None` starts from the current tip
+    next_round: Option<u64>,
+    /// Upper bound on rounds relayed per tick, so a long outage is caught up gradually
+    max_batch: u64,
+}
+
+impl<S: BeaconSource, T: Submitter> Relayer<S, T> {
+    pub fn new(source: S, submitter: T, metrics: Arc<Metrics>) -> Self {
+        Relayer {
+            source,
+            submitter,
+            metrics,
+            verifier: verify_quicknet_beacon,
+            next_round: None,
+            max_batch: 20,
+        }
+    }
+
+    pub fn starting_at(mut self, round: u64) -> Self {
+        self.next_round = Some(round);
+        self
+    }
+
+    pub fn with_max_batch(mut self, max_batch: u64) -> Self {
+        self.max_batch = max_batch.max(1);
+        self
+    }
+
+    /// Swap the signature check, e.g. for a network other than quicknet
+    pub fn with_verifier(mut self, verifier: Verifier) -> Self {
+        self.verifier = verifier;
+        self
+    }
+
+    pub fn next_round(&self) -> Option<u64> {
+        self.next_round
+    }
+
+    pub fn submitter(&self) -> &T {
+        &self.submitter
+    }
+
+    /// Relay every round between the last relayed round and the current tip.
+    /// Rounds behind the tip are fetched individually (gap-filling). Whatever was
+    /// verified before an error is still submitted, so progress is never lost.
+    pub fn tick(&mut self) -> Result<usize, RelayError> {
+        let latest = self.fetch(|source| source.latest())?;
+        let start = self.next_round.unwrap_or(latest.round);
+
+        if latest.round < start {
+            return Ok(0);
+        }
+        let end = latest.round.min(start + self.max_batch - 1);
+
+        let mut msgs = vec![];
+        let mut failure = None;
+
+        for round in start..=end {
+            let beacon = if round == latest.round {
+                latest.clone()
+            } else {
+                match self.fetch(|source| source.round(round)) {
+                    Ok(beacon) => {
+                        Metrics::inc(&self.metrics.gaps_filled);
+                        beacon
+                    }
+                    Err(e) => {
+                        failure = Some(e);
+                        break;
+                    }
+                }
+            };
+
+            match self.build_msg(&beacon) {
+                Ok(msg) => msgs.push(msg),
+                Err(e) => {
+                    Metrics::inc(&self.metrics.verification_failures);
+                    failure = Some(e);
+                    break;
+                }
+            }
+        }
+
+        let relayed = msgs.len();
+        if relayed > 0 {
+            if let Err(e) = self.submitter.submit(&msgs) {
+                Metrics::inc(&self.metrics.submit_errors);
+                return Err(e.into());
+            }
+
+            let last = start + relayed as u64 - 1;
+            self.next_round = Some(last + 1);
+            Metrics::add(&self.metrics.beacons_submitted, relayed as u64);
+            self.metrics
+                .last_submitted_round
+                .store(last, std::sync::atomic::Ordering::Relaxed);
+        }
+
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(relayed),
+        }
+    }
+
+    /// Tick forever: wait `poll` after a successful tick, back off after a failed one
+    pub fn run(&mut self, poll: Duration, backoff: &mut Backoff) -> ! {
+        loop {
+            match self.tick() {
+                Ok(relayed) => {
+                    if relayed > 0 {
+                        eprintln!(
+                            "relayed {} beacon(s), next round {:?}",
+                            relayed, self.next_round
+                        );
+                    }
+                    backoff.reset();
+                    thread::sleep(poll);
+                }
+                Err(e) => {
+                    let delay = backoff.next_delay();
+                    eprintln!("relay failed: {} (retrying in {:?})", e, delay);
+                    thread::sleep(delay);
+                }
+            }
+        }
+    }
+
+    fn fetch<F>(&self, f: F) -> Result<DrandBeacon, RelayError>
+    where
+        F: FnOnce(&S) -> Result<DrandBeacon, FetchError>,
+    {
+        let beacon = f(&self.source).inspect_err(|_| Metrics::inc(&self.metrics.fetch_errors))?;
+        Metrics::inc(&self.metrics.beacons_fetched);
+        Ok(beacon)
+    }
+
+    fn build_msg(&self, beacon: &DrandBeacon) -> Result<ExecuteMsg, RelayError> {
+        let invalid = |msg: String| RelayError::Verification {
+            round: beacon.round,
+            msg,
+        };
+
+        let signature = hex::decode(&beacon.signature).map_err(|e| invalid(e.to_string()))?;
+        let claimed = hex::decode(&beacon.randomness).map_err(|e| invalid(e.to_string()))?;
+
+        let randomness = (self.verifier)(beacon.round, &signature).map_err(invalid)?;
+        if randomness[..] != claimed[..] {
+            return Err(invalid("randomness does not match signature".to_string()));
+        }
+
+        Ok(ExecuteMsg::AddBeacon {
+            round: Uint64::new(beacon.round),
+            signature: HexBinary::from(signature),
+            randomness: HexBinary::from(randomness.as_slice()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drand::{HttpSource, QUICKNET_CHAIN_HASH};
+    use crate::standin::DrandStandIn;
+    use crate::submitter::MockSubmitter;
+    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env};
+    use cosmwasm_std::{from_json, Addr, Decimal, Uint128};
+    use drand_oracle::msg::{BeaconResponse, InstantiateMsg, QueryMsg};
+    use sha2::{Digest, Sha256};
+
+    const ROUND: u64 = 1000;
+    const SIGNATURE: &str = "b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39";
+    const RANDOMNESS: &str = "fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd";
+    // Valid signature, but for round 123
+    const OTHER_SIGNATURE: &str = "b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92";
+
+    /// Stand-in for the BLS check so synthetic beacons can be used for any round
+    fn fake_verify(_round: u64, signature: &[u8]) -> Result<[u8; 32], String> {
+        Ok(Sha256::digest(signature).into())
+    }
+
+    fn synthetic_beacon(round: u64) -> DrandBeacon {
+        let signature = round.to_be_bytes().repeat(6);
+        DrandBeacon {
+            round,
+            randomness: hex::encode(Sha256::digest(&signature)),
+            signature: hex::encode(signature),
+        }
+    }
+
+    fn relayer_for(
+        standin: &DrandStandIn,
+        submitter: MockSubmitter,
+    ) -> (Relayer<HttpSource, MockSubmitter>, Arc<Metrics>) {
+        let source = HttpSource::new(
+            &standin.base_url,
+            QUICKNET_CHAIN_HASH,
+            Duration::from_secs(5),
+        );
+        let metrics = Arc::new(Metrics::default());
+        (Relayer::new(source, submitter, metrics.clone()), metrics)
+    }
+
+    fn rounds(submitter: &MockSubmitter) -> Vec<u64> {
+        submitter
+            .submitted
+            .iter()
+            .map(|msg| match msg {
+                ExecuteMsg::AddBeacon { round, .. } => round.u64(),
+                other => panic!("unexpected message {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn verifier_follows_chain_hash() {
+        let sig = hex::decode(SIGNATURE).unwrap();
+        let verify = verifier_for(QUICKNET_CHAIN_HASH).unwrap();
+        assert_eq!(hex::encode(verify(ROUND, &sig).unwrap()), RANDOMNESS);
+        // Mainnet's default chain
+        assert!(
+            verifier_for("8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn relays_real_beacon_into_oracle() {
+        let standin = DrandStandIn::start(QUICKNET_CHAIN_HASH).unwrap();
+        standin.publish(DrandBeacon {
+            round: ROUND,
+            randomness: RANDOMNESS.to_string(),
+            signature: SIGNATURE.to_string(),
+        });
+
+        let (mut relayer, metrics) = relayer_for(&standin, MockSubmitter::default());
+        assert_eq!(relayer.tick().unwrap(), 1);
+        assert_eq!(relayer.next_round(), Some(ROUND + 1));
+        assert_eq!(Metrics::get(&metrics.last_submitted_round), ROUND);
+
+        // The oracle accepts exactly what the relayer submitted
+        let mut deps = mock_dependencies();
+        let info = message_info(&Addr::unchecked("relayer"), &[]);
+        drand_oracle::contract::instantiate(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            InstantiateMsg {
+                fee_denom: "inj".to_string(),
+                base_fee: Uint128::zero(),
+                gas_price: Decimal::zero(),
+                network: None,
+            },
+        )
+        .unwrap();
+        for msg in &relayer.submitter().submitted {
+            drand_oracle::contract::execute(deps.as_mut(), mock_env(), info.clone(), msg.clone())
+                .unwrap();
+        }
+
+        let res = drand_oracle::contract::query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Beacon {
+                round: Uint64::new(ROUND),
+            },
+        )
+        .unwrap();
+        let beacon: BeaconResponse = from_json(res).unwrap();
+        assert_eq!(hex::encode(beacon.uniform_seed), RANDOMNESS);
+    }
+
+    #[test]
+    fn rejects_invalid_signature() {
+        let standin = DrandStandIn::start(QUICKNET_CHAIN_HASH).unwrap();
+        standin.publish(DrandBeacon {
+            round: ROUND,
+            randomness: RANDOMNESS.to_string(),
+            signature: OTHER_SIGNATURE.to_string(),
+        });
+
+        let (mut relayer, metrics) = relayer_for(&standin, MockSubmitter::default());
+        let err = relayer.tick().unwrap_err();
+        assert!(matches!(err, RelayError::Verification { round: ROUND, .. }));
+        assert_eq!(Metrics::get(&metrics.verification_failures), 1);
+        assert!(relayer.submitter().submitted.is_empty());
+        assert_eq!(relayer.next_round(), None);
+    }
+
+    #[test]
+    fn fills_gaps_up_to_tip() {
+        let standin = DrandStandIn::start(QUICKNET_CHAIN_HASH).unwrap();
+        for round in 5..=9 {
+            standin.publish(synthetic_beacon(round));
+        }
+
+        let (relayer, metrics) = relayer_for(&standin, MockSubmitter::default());
+        let mut relayer = relayer.with_verifier(fake_verify).starting_at(5);
+
+        assert_eq!(relayer.tick().unwrap(), 5);
+        assert_eq!(rounds(relayer.submitter()), vec![5, 6, 7, 8, 9]);
+        assert_eq!(Metrics::get(&metrics.gaps_filled), 4);
+        assert_eq!(relayer.next_round(), Some(10));
+
+        // Nothing new at the tip
+        assert_eq!(relayer.tick().unwrap(), 0);
+    }
+
+    #[test]
+    fn catches_up_in_batches() {
+        let standin = DrandStandIn::start(QUICKNET_CHAIN_HASH).unwrap();
+        for round in 1..=5 {
+            standin.publish(synthetic_beacon(round));
+        }
+
+        let (relayer, _) = relayer_for(&standin, MockSubmitter::default());
+        let mut relayer = relayer
+            .with_verifier(fake_verify)
+            .with_max_batch(2)
+            .starting_at(1);
+
+        assert_eq!(relayer.tick().unwrap(), 2);
+        assert_eq!(relayer.tick().unwrap(), 2);
+        assert_eq!(relayer.tick().unwrap(), 1);
+        assert_eq!(rounds(relayer.submitter()), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn missing_round_is_resumed_on_next_tick() {
+        let standin = DrandStandIn::start(QUICKNET_CHAIN_HASH).unwrap();
+        for round in [5, 6, 8] {
+            standin.publish(synthetic_beacon(round));
+        }
+
+        let (relayer, metrics) = relayer_for(&standin, MockSubmitter::default());
+        let mut relayer = relayer.with_verifier(fake_verify).starting_at(5);
+
+        // Rounds before the hole are still submitted
+        let err = relayer.tick().unwrap_err();
+        assert_eq!(err, RelayError::Fetch(FetchError::NotFound(7)));
+        assert_eq!(rounds(relayer.submitter()), vec![5, 6]);
+        assert_eq!(relayer.next_round(), Some(7));
+        assert_eq!(Metrics::get(&metrics.fetch_errors), 1);
+
+        standin.publish(synthetic_beacon(7));
+        assert_eq!(relayer.tick().unwrap(), 2);
+        assert_eq!(rounds(relayer.submitter()), vec![5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn failed_submission_is_retried() {
+        let standin = DrandStandIn::start(QUICKNET_CHAIN_HASH).unwrap();
+        standin.publish(synthetic_beacon(3));
+        standin.publish(synthetic_beacon(4));
+
+        let submitter = MockSubmitter {
+            fail_next: 1,
+            ..MockSubmitter::default()
+        };
+        let (relayer, metrics) = relayer_for(&standin, submitter);
+        let mut relayer = relayer.with_verifier(fake_verify).starting_at(3);
+
+        assert!(matches!(relayer.tick(), Err(RelayError::Submit(_))));
+        assert_eq!(relayer.next_round(), Some(3));
+        assert_eq!(Metrics::get(&metrics.submit_errors), 1);
+
+        assert_eq!(relayer.tick().unwrap(), 2);
+        assert_eq!(rounds(relayer.submitter()), vec![3, 4]);
+        assert_eq!(Metrics::get(&metrics.beacons_submitted), 2);
+    }
+
+    #[test]
+    fn unavailable_api_is_retried() {
+        let standin = DrandStandIn::start(QUICKNET_CHAIN_HASH).unwrap();
+        standin.publish(synthetic_beacon(1));
+        standin.fail_next(1);
+
+        let (relayer, metrics) = relayer_for(&standin, MockSubmitter::default());
+        let mut relayer = relayer.with_verifier(fake_verify);
+
+        assert!(matches!(
+            relayer.tick(),
+            Err(RelayError::Fetch(FetchError::Http(_)))
+        ));
+        assert_eq!(Metrics::get(&metrics.fetch_errors), 1);
+
+        assert_eq!(relayer.tick().unwrap(), 1);
+        assert!(metrics
+            .render()
+            .contains("drand_relayer_beacons_submitted_total 1\n"));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(5));
+        let delays: Vec<u64> = (0..5).map(|_| backoff.next_delay().as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 5, 5]);
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::BTreeMap;
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::drand::DrandBeacon;
+use crate::http;
+
+/// Local stand-in for the drand HTTP API so the relayer can run fully offline.
+/// Serves `/{chain_hash}/public/latest` and `/{chain_hash}/public/{round}` from
+/// an in-memory set of beacons, and can be told to fail the next N requests.
+pub struct DrandStandIn {
+    pub base_url: String,
+    beacons: Arc<Mutex<BTreeMap<u64, DrandBeacon>>>,
+    fail_next: Arc<AtomicUsize>,
+}
+
+impl DrandStandIn {
+    pub fn start(chain_hash: &str) -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let base_url = format!("http://{}", listener.local_addr()?);
+
+        let beacons: Arc<Mutex<BTreeMap<u64, DrandBeacon>>> = Arc::default();
+        let fail_next = Arc::new(AtomicUsize::new(0));
+
+        let prefix = format!("/{}/public/", chain_hash);
+        let served = beacons.clone();
+        let failures = fail_next.clone();
+
+        http::serve(listener, move |path| {
+            let claimed = failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok();
+            if claimed {
+                return (500, r#"{"error":"stand-in failure"}"#.to_string());
+            }
+
+            let Some(which) = path.strip_prefix(&prefix) else {
+                return (404, String::new());
+            };
+
+            let beacons = served.lock().unwrap();
+            let beacon = match which {
+                "latest" => beacons.values().next_back(),
+                round => round.parse::<u64>().ok().and_then(|r| beacons.get(&r)),
+            };
+
+            match beacon {
+                Some(beacon) => (200, serde_json::to_string(beacon).unwrap()),
+                None => (404, String::new()),
+            }
+        });
+
+        Ok(DrandStandIn {
+            base_url,
+            beacons,
+            fail_next,
+        })
+    }
+
+    pub fn publish(&self, beacon: DrandBeacon) {
+        self.beacons.lock().unwrap().insert(beacon.round, beacon);
+    }
+
+    pub fn fail_next(&self, count: usize) {
+        self.fail_next.store(count, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,261 @@
+use drand_oracle::msg::ExecuteMsg;
+use serde::Deserialize;
+use std::process::{Command, Output};
+use std::thread;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SubmitError {
+    #[error("failed to encode message: {0}")]
+    Encode(String),
+
+    #[error("submission rejected: {0}")]
+    Rejected(String),
+
+    #[error("transaction {0} was not included in time")]
+    Timeout(String),
+}
+
+/// Delivers `AddBeacon` messages to the oracle contract.
+/// Messages are handed over in ascending round order; a submitter should either
+/// accept the whole batch or return an error so the relayer can retry it.
+pub trait Submitter {
+    fn submit(&mut self, msgs: &[ExecuteMsg]) -> Result<(), SubmitError>;
+}
+
+impl<T: Submitter + ?Sized> Submitter for Box<T> {
+    fn submit(&mut self, msgs: &[ExecuteMsg]) -> Result<(), SubmitError> {
+        (**self).submit(msgs)
+    }
+}
+
+/// Prints each message as a JSON line instead of broadcasting it (dry run)
+pub struct StdoutSubmitter;
+
+impl Submitter for StdoutSubmitter {
+    fn submit(&mut self, msgs: &[ExecuteMsg]) -> Result<(), SubmitError> {
+        for msg in msgs {
+            let json =
+                serde_json::to_string(msg).map_err(|e| SubmitError::Encode(e.to_string()))?;
+            println!("{}", json);
+        }
+        Ok(())
+    }
+}
+
+/// Broadcasts through a chain CLI, the same way the deploy scripts do:
+/// `<binary> tx wasm execute <oracle> <msg> --from <key> ... --yes`
+///
+/// Each transaction is polled with `<binary> query tx <hash>` until it is included
+/// before the next one is broadcast, so the CLI always signs with the account's
+/// current sequence. A non-zero result code on broadcast or inclusion fails the batch.
+pub struct CliSubmitter {
+    pub binary: String,
+    pub oracle: String,
+    /// Extra flags such as `--from`, `--chain-id`, `--node`, `--fees` and `--gas`
+    pub flags: Vec<String>,
+    /// Delay between inclusion queries
+    pub poll: Duration,
+    /// How long to wait for a transaction to be included
+    pub timeout: Duration,
+}
+
+/// The fields of the CLI's JSON tx response the submitter looks at
+#[derive(Deserialize)]
+struct TxResponse {
+    txhash: String,
+    #[serde(default)]
+    code: u32,
+    #[serde(default)]
+    raw_log: String,
+}
+
+impl TxResponse {
+    fn parse(output: &Output) -> Result<Self, SubmitError> {
+        if !output.status.success() {
+            return Err(SubmitError::Rejected(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        let response: TxResponse = serde_json::from_slice(&output.stdout)
+            .map_err(|e| SubmitError::Rejected(format!("unreadable tx response: {}", e)))?;
+        if response.code != 0 {
+            return Err(SubmitError::Rejected(format!(
+                "tx {} failed with code {}: {}",
+                response.txhash, response.code, response.raw_log
+            )));
+        }
+        Ok(response)
+    }
+}
+
+impl CliSubmitter {
+    /// `--node` is the only one of `flags` that `query tx` needs
+    fn node_flags(&self) -> Vec<&str> {
+        let mut node = vec![];
+        let mut flags = self.flags.iter();
+        while let Some(flag) = flags.next() {
+            if flag == "--node" {
+                node.push(flag.as_str());
+                node.extend(flags.next().map(String::as_str));
+            } else if flag.starts_with("--node=") {
+                node.push(flag.as_str());
+            }
+        }
+        node
+    }
+
+    fn broadcast(&self, msg: &ExecuteMsg) -> Result<String, SubmitError> {
+        let json = serde_json::to_string(msg).map_err(|e| SubmitError::Encode(e.to_string()))?;
+
+        let output = Command::new(&self.binary)
+            .args(["tx", "wasm", "execute", &self.oracle, &json])
+            .args(&self.flags)
+            .args(["--output", "json", "--yes"])
+            .output()
+            .map_err(|e| SubmitError::Rejected(e.to_string()))?;
+
+        Ok(TxResponse::parse(&output)?.txhash)
+    }
+
+    fn wait_for_inclusion(&self, txhash: &str) -> Result<(), SubmitError> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let output = Command::new(&self.binary)
+                .args(["query", "tx", txhash])
+                .args(self.node_flags())
+                .args(["--output", "json"])
+                .output()
+                .map_err(|e| SubmitError::Rejected(e.to_string()))?;
+
+            // The query fails until the tx is in a block
+            if output.status.success() {
+                return TxResponse::parse(&output).map(|_| ());
+            }
+            if Instant::now() >= deadline {
+                return Err(SubmitError::Timeout(txhash.to_string()));
+            }
+            thread::sleep(self.poll);
+        }
+    }
+}
+
+impl Submitter for CliSubmitter {
+    fn submit(&mut self, msgs: &[ExecuteMsg]) -> Result<(), SubmitError> {
+        for msg in msgs {
+            let txhash = self.broadcast(msg)?;
+            self.wait_for_inclusion(&txhash)?;
+        }
+        Ok(())
+    }
+}
+
+/// Records submitted messages in memory. Can be told to reject the next N batches.
+#[derive(Default)]
+pub struct MockSubmitter {
+    pub submitted: Vec<ExecuteMsg>,
+    pub fail_next: usize,
+}
+
+impl Submitter for MockSubmitter {
+    fn submit(&mut self, msgs: &[ExecuteMsg]) -> Result<(), SubmitError> {
+        if self.fail_next > 0 {
+            self.fail_next -= 1;
+            return Err(SubmitError::Rejected("mock failure".to_string()));
+        }
+        self.submitted.extend_from_slice(msgs);
+        Ok(())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::{Path, PathBuf};
+
+    /// Writes a fake chain CLI that logs its arguments, accepts every broadcast and
+    /// reports the tx as included with `code` on the second `query tx`
+    fn fake_cli(name: &str, code: u32) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("drand-relayer-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("chaind");
+        let script = format!(
+            r#"#!/bin/sh
+echo "$@" >> "$0.log"
+case "$1" in
+  tx) echo '{{"txhash":"AB12","code":0,"raw_log":""}}' ;;
+  query)
+    if [ -f "$0.included" ]; then
+      echo '{{"txhash":"AB12","code":{},"raw_log":"out of gas"}}'
+    else
+      touch "$0.included"
+      echo "tx (AB12) not found" >&2
+      exit 1
+    fi ;;
+esac
+"#,
+            code
+        );
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn submitter_for(binary: &Path) -> CliSubmitter {
+        CliSubmitter {
+            binary: binary.to_string_lossy().to_string(),
+            oracle: "oracle".to_string(),
+            flags: ["--from", "relayer", "--node", "http://node:26657"]
+                .map(String::from)
+                .to_vec(),
+            poll: Duration::from_millis(10),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    fn log(binary: &Path) -> Vec<String> {
+        let log = fs::read_to_string(binary.with_extension("log")).unwrap();
+        log.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn waits_for_each_tx_to_be_included() {
+        let binary = fake_cli("included", 0);
+        let mut submitter = submitter_for(&binary);
+
+        submitter
+            .submit(&[ExecuteMsg::NextBeacon, ExecuteMsg::NextBeacon])
+            .unwrap();
+
+        // The second broadcast only happens once the first tx is in a block
+        let log = log(&binary);
+        let commands: Vec<&str> = log.iter().map(|line| &line[..2]).collect();
+        assert_eq!(commands, vec!["tx", "qu", "qu", "tx", "qu"]);
+        assert_eq!(
+            log[1],
+            "query tx AB12 --node http://node:26657 --output json"
+        );
+        assert!(log[0].ends_with("--from relayer --node http://node:26657 --output json --yes"));
+    }
+
+    #[test]
+    fn failed_tx_code_rejects_the_batch() {
+        let binary = fake_cli("failed", 11);
+        let mut submitter = submitter_for(&binary);
+
+        let err = submitter
+            .submit(&[ExecuteMsg::NextBeacon, ExecuteMsg::NextBeacon])
+            .unwrap_err();
+        assert_eq!(
+            err,
+            SubmitError::Rejected("tx AB12 failed with code 11: out of gas".to_string())
+        );
+        // Nothing else is broadcast after the failure
+        assert_eq!(log(&binary).len(), 3);
+    }
+}
@@ -220,7 +220,8 @@ if [ "$SKIP_DEPLOY" = false ]; then
 
     # --- Instantiate Oracle ---
     echo "[3/5] Instantiating Drand Oracle..."
-    ORACLE_ADDRESS=$(instantiate_contract "$ORACLE_CODE_ID" '{}' "spore-oracle-v3" "$DEPLOYER_ADDRESS")
+    INIT_ORACLE='{"fee_denom":"inj","base_fee":"0","gas_price":"160000000"}'
+    ORACLE_ADDRESS=$(instantiate_contract "$ORACLE_CODE_ID" "$INIT_ORACLE" "spore-oracle-v3" "$DEPLOYER_ADDRESS")
     echo "  Oracle: $ORACLE_ADDRESS"
     echo ""
 
@@ -234,10 +235,9 @@ if [ "$SKIP_DEPLOY" = false ]; then
       --arg oracle "$ORACLE_ADDRESS" \
       --arg cw721 "$CW721_ADDRESS" \
       '{
-        payment_denom: $payment,
+        payment: { native: $payment },
         spin_cost: $cost,
-        mint_cost: $mint_cost,
-        mint_cost_increment: $slope,
+        mint_pricing: { linear: { base: $mint_cost, increment: $slope } },
         oracle_addr: $oracle,
         cw721_addr: $cw721
       }')
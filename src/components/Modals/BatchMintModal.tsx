@@ -12,45 +12,32 @@ interface Props {
 
 export const BatchMintModal: React.FC<Props> = ({ isOpen, onClose, onConfirm, isLoading }) => {
     const [count, setCount] = useState(1);
-    const [basePrice, setBasePrice] = useState<bigint>(BigInt(0));
-    const [increment, setIncrement] = useState<bigint>(BigInt(0));
     const [totalCost, setTotalCost] = useState<string>('0');
 
-    // Fetch pricing data when modal opens
+    // Quote the batch from the contract's pricing curve
     useEffect(() => {
-        if (isOpen) {
-            const fetchData = async () => {
-                try {
-                    // 1. Get the current price from the bonding curve
-                    const price = await shroomService.getCurrentMintPrice();
+        if (!isOpen) return;
 
-                    // 2. Get the slope (increment) from the contract config
-                    const config = await shroomService.getGameConfig();
+        let cancelled = false;
+        const fetchQuote = async () => {
+            try {
+                const prices = await shroomService.quoteMint(count);
+                const total = prices.reduce((sum, price) => sum + BigInt(price), BigInt(0));
 
-                    setBasePrice(BigInt(price));
-                    setIncrement(BigInt(config?.mint_cost_increment || "0"));
-                } catch (error) {
-                    console.error("Failed to fetch batch pricing:", error);
-                    // Fallback to safety defaults
-                    setBasePrice(BigInt(0));
-                    setIncrement(BigInt(0));
-                }
-            };
-            fetchData();
-        }
-    }, [isOpen]);
+                // Format for display
+                const readable = (Number(total) / Math.pow(10, NETWORK_CONFIG.paymentDecimals)).toFixed(2);
+                if (!cancelled) setTotalCost(readable);
+            } catch (error) {
+                console.error("Failed to fetch batch pricing:", error);
+                if (!cancelled) setTotalCost('0');
+            }
+        };
+        fetchQuote();
 
-    // Recalculate total cost when count changes
-    useEffect(() => {
-        let total = BigInt(0);
-        for (let i = 0; i < count; i++) {
-            total += basePrice + (increment * BigInt(i));
-        }
-
-        // Format for display
-        const readable = (Number(total) / Math.pow(10, NETWORK_CONFIG.paymentDecimals)).toFixed(2);
-        setTotalCost(readable);
-    }, [count, basePrice, increment]);
+        return () => {
+            cancelled = true;
+        };
+    }, [isOpen, count]);
 
     if (!isOpen) return null;
 
@@ -335,7 +335,21 @@ export const shroomService = {
     },
 
     /**
-     * NEW: Fetch contract config to get the bonding curve slope (increment)
+     * Quote the next `quantity` mints from the contract's pricing curve.
+     * Returns the price of each mint, in order.
+     */
+    async quoteMint(quantity: number): Promise<string[]> {
+        const queryMsg = { quote_mint: { quantity } };
+        const response = await wasmApi.fetchSmartContractState(
+            NETWORK_CONFIG.gameControllerAddress,
+            queryMsg
+        );
+        const data = JSON.parse(new TextDecoder().decode(response.data));
+        return data.prices;
+    },
+
+    /**
+     * Fetch the game controller config
      */
     async getGameConfig(): Promise<any> {
         try {
@@ -598,16 +612,12 @@ export const shroomService = {
      * All requests target the same drand round and can be resolved together.
      */
     async makeBatchRequestMintMsgs(userAddress: string, count: number) {
-        const priceResponse = await this.getCurrentMintPrice();
-        const config = await this.getGameConfig();
-
-        const currentPrice = BigInt(priceResponse);
-        const increment = BigInt(config?.mint_cost_increment || "0");
+        const prices = await this.quoteMint(count);
 
         const msgs = [];
 
         for (let i = 0; i < count; i++) {
-            const specificPrice = currentPrice + increment * BigInt(i);
+            const specificPrice = BigInt(prices[i]);
 
             msgs.push(
                 new MsgExecuteContract({